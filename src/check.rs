@@ -0,0 +1,278 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeSet,
+    fs,
+    mem::size_of,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
+
+use idx_binary::{AvltrieeSearch, AvltrieeUpdate, IdxFile};
+use idx_file::{AvltrieeNode, IdxFileAllocator};
+use various_data_file::DataAddress;
+
+use crate::{
+    field, manifest::FieldChange, open_idx_file, Activity, Data, Field, FieldName, Result, RowSet,
+};
+
+/// Problems found by [Data::check].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CheckReport {
+    /// Rows that are in an index but not in the serial index, by index name.
+    pub orphan_rows: Vec<(String, Vec<NonZeroU32>)>,
+    /// Rows in the serial index that are missing from an index every row must have, by index name.
+    pub missing_rows: Vec<(String, Vec<NonZeroU32>)>,
+    /// Rows listed more than once in the blank list.
+    pub duplicated_blanks: Vec<NonZeroU32>,
+    /// Rows listed in the blank list that are in use.
+    pub used_blanks: Vec<NonZeroU32>,
+    /// Indexes whose tree is out of order or does not visit each of its nodes once.
+    pub broken_indexes: Vec<String>,
+}
+
+impl CheckReport {
+    /// Returns true if no problem was found.
+    pub fn is_ok(&self) -> bool {
+        self.orphan_rows.is_empty()
+            && self.missing_rows.is_empty()
+            && self.duplicated_blanks.is_empty()
+            && self.used_blanks.is_empty()
+            && self.broken_indexes.is_empty()
+    }
+}
+
+/// Returns the rows that have a node in the index file.
+fn nodes<T, I: ?Sized>(triee: &idx_file::IdxFileAvlTriee<T, I>, path: &Path) -> RowSet {
+    let capacity = fs::metadata(path).map_or(0, |m| m.len() / size_of::<AvltrieeNode<T>>() as u64);
+    (1..capacity)
+        .filter_map(|row| NonZeroU32::new(row as u32))
+        .filter(|row| triee.node(*row).is_some())
+        .collect()
+}
+
+/// Iterates the tree in order and checks that it visits every node once and that the values never decrease.
+/// Values may repeat, because rows with the same value hang from one node.
+fn is_broken<T, I: ?Sized, S: AvltrieeSearch<T, I, IdxFileAllocator<T>>>(
+    index: &S,
+    nodes: &RowSet,
+) -> bool {
+    let mut visited = RowSet::new();
+    let mut before: Option<&I> = None;
+    for row in index.as_ref().iter().take(nodes.len() + 1) {
+        if !nodes.contains(&row) || !visited.insert(row) {
+            return true;
+        }
+        let value = unsafe { index.value_unchecked(row) };
+        if before.is_some_and(|before| S::cmp(before, value) == Ordering::Greater) {
+            return true;
+        }
+        before = Some(value);
+    }
+    visited.len() != nodes.len()
+}
+
+impl Data {
+    fn index_path(&self, file_name: &str) -> PathBuf {
+        self.dir().join(file_name)
+    }
+
+    /// Checks that all indexes agree on which rows exist and that their trees are intact.
+    pub fn check(&self) -> CheckReport {
+        let mut report = CheckReport::default();
+
+        let serial_nodes = nodes(&self.serial, &self.index_path("serial.i"));
+        if is_broken(&**self.serial, &serial_nodes) {
+            report.broken_indexes.push("serial".into());
+        }
+        let mut blanks = BTreeSet::new();
+        for row in self.serial.blanks() {
+            if !blanks.insert(row) {
+                report.duplicated_blanks.push(row);
+            }
+            if serial_nodes.contains(&row) {
+                report.used_blanks.push(row);
+            }
+        }
+
+        let mut check = |name: String, nodes: RowSet, broken: bool, required: bool| {
+            if broken {
+                report.broken_indexes.push(name.clone());
+            }
+            let orphans: Vec<_> = (&nodes - &serial_nodes).into_iter().collect();
+            if !orphans.is_empty() {
+                report.orphan_rows.push((name.clone(), orphans));
+            }
+            if required {
+                let missing: Vec<_> = (&serial_nodes - &nodes).into_iter().collect();
+                if !missing.is_empty() {
+                    report.missing_rows.push((name, missing));
+                }
+            }
+        };
+        macro_rules! check_index {
+            ($index:expr, $name:literal) => {
+                if let Some(ref f) = $index {
+                    let nodes = nodes(f, &self.index_path(concat!($name, ".i")));
+                    let broken = is_broken(&**f, &nodes);
+                    check($name.into(), nodes, broken, true);
+                }
+            };
+        }
+        check_index!(self.uuid, "uuid");
+        check_index!(self.activity, "activity");
+        check_index!(self.term_begin, "term_begin");
+        check_index!(self.term_end, "term_end");
+        check_index!(self.last_updated, "last_updated");
+
+        let mut names: Vec<_> = self.fields.keys().collect();
+        names.sort();
+        for name in names {
+            let field = self.fields.get(name).unwrap();
+            let nodes = nodes(
+                field.as_ref(),
+                &self.fields_dir.join(name.as_str()).join(".i"),
+            );
+            let broken = is_broken(field, &nodes);
+            check(format!("fields/{}", name), nodes, broken, false);
+        }
+
+        report
+    }
+
+    /// Fixes the problems found by [Data::check] and returns them.
+    /// Broken indexes are rebuilt from their nodes, orphan rows are removed from indexes,
+    /// and rows missing from an index get the value an insert with default arguments would give them.
+    pub fn repair(&mut self) -> Result<CheckReport> {
+        let report = self.check();
+
+        for name in &report.broken_indexes {
+            match name.as_str() {
+                "serial" => {
+                    let path = self.index_path("serial.i");
+                    Self::rebuild_idx_file(
+                        self.serial.index_mut(),
+                        path,
+                        self.option.allocation_lot,
+                    )?;
+                }
+                "uuid" => self.rebuild_option(name, |data| &mut data.uuid)?,
+                "activity" => self.rebuild_option(name, |data| &mut data.activity)?,
+                "term_begin" => self.rebuild_option(name, |data| &mut data.term_begin)?,
+                "term_end" => self.rebuild_option(name, |data| &mut data.term_end)?,
+                "last_updated" => self.rebuild_option(name, |data| &mut data.last_updated)?,
+                _ => {
+                    if let Some(name) = name.strip_prefix("fields/") {
+                        self.rebuild_field(name)?;
+                    }
+                }
+            }
+        }
+        self.serial.repair_blanks()?;
+
+        for (name, rows) in &report.orphan_rows {
+            for row in rows {
+                match name.as_str() {
+                    "uuid" => self.uuid.as_mut().unwrap().delete(*row),
+                    "activity" => self.activity.as_mut().unwrap().delete(*row),
+                    "term_begin" => self.term_begin.as_mut().unwrap().delete(*row),
+                    "term_end" => self.term_end.as_mut().unwrap().delete(*row),
+                    "last_updated" => self.last_updated.as_mut().unwrap().delete(*row),
+                    _ => {
+                        if let Some(name) = name.strip_prefix("fields/") {
+                            let name = FieldName::new(name.into());
+                            self.reindex_text(&name, *row, None);
+                            if let Some(field) = self.fields.get_mut(&name) {
+                                field.delete(*row);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let now = Self::now();
+        for (name, rows) in &report.missing_rows {
+            for row in rows {
+                match name.as_str() {
+                    "uuid" => self
+                        .uuid
+                        .as_mut()
+                        .unwrap()
+                        .update(*row, &crate::create_uuid()),
+                    "activity" => self
+                        .activity
+                        .as_mut()
+                        .unwrap()
+                        .update(*row, &(Activity::default() as u8)),
+                    "term_begin" => self.term_begin.as_mut().unwrap().update(*row, &now),
+                    "term_end" => self.term_end.as_mut().unwrap().update(*row, &0),
+                    "last_updated" => self.last_updated.as_mut().unwrap().update(*row, &now),
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn rebuild_option<T: Clone + Ord>(
+        &mut self,
+        name: &str,
+        index: impl Fn(&mut Self) -> &mut Option<IdxFile<T>>,
+    ) -> Result<()> {
+        let path = self.index_path(&(name.to_owned() + ".i"));
+        let allocation_lot = self.option.allocation_lot;
+        if let Some(f) = index(self) {
+            Self::rebuild_idx_file(f, path, allocation_lot)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the nodes of the index into a new file and replaces the index with it.
+    fn rebuild_idx_file<T: Clone + Ord>(
+        index: &mut IdxFile<T>,
+        path: PathBuf,
+        allocation_lot: u32,
+    ) -> Result<()> {
+        let mut rebuild_path = path.clone().into_os_string();
+        rebuild_path.push(".rebuild");
+        let rebuild_path = PathBuf::from(rebuild_path);
+        if rebuild_path.exists() {
+            fs::remove_file(&rebuild_path)?;
+        }
+        let mut rebuilt: IdxFile<T> = open_idx_file(rebuild_path.clone(), allocation_lot)?;
+        for row in nodes(index, &path) {
+            rebuilt.update(row, unsafe { index.value_unchecked(row) });
+        }
+        drop(rebuilt);
+        fs::rename(&rebuild_path, &path)?;
+        *index = open_idx_file(path, allocation_lot)?;
+        Ok(())
+    }
+
+    /// Writes the values of the field into a new directory and replaces the field with it.
+    /// The replacement is recorded in the manifest first, so that opening the data finishes it if the process stops.
+    fn rebuild_field(&mut self, name: &str) -> Result<()> {
+        let path = self.fields_dir.join(name);
+        let rebuild_path = field::rebuild_dir(&self.fields_dir);
+        if rebuild_path.exists() {
+            fs::remove_dir_all(&rebuild_path)?;
+        }
+        fs::create_dir_all(&rebuild_path)?;
+        let allocation_lot = self.option.allocation_lot;
+        let key = FieldName::new(name.to_string());
+        if let Some(field) = self.fields.get_mut(&key) {
+            let mut rebuilt: Field = field::open(rebuild_path.clone(), allocation_lot)?;
+            for row in nodes::<DataAddress, [u8]>(field.as_ref(), &path.join(".i")) {
+                rebuilt.update(row, unsafe { field.value_unchecked(row) });
+            }
+            drop(rebuilt);
+            let change = FieldChange::Replace(key.clone());
+            self.manifest.begin_replace(&key)?;
+            field::change_dir(&self.fields_dir, &change)?;
+            self.manifest.finish_change()?;
+            *field = field::open(path, allocation_lot)?;
+        }
+        Ok(())
+    }
+}
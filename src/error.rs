@@ -0,0 +1,52 @@
+use std::{fmt, io, num::NonZeroU32, path::PathBuf};
+
+use crate::FieldName;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed, or the mmap could not be created.
+    Io(io::Error),
+    /// The file exists but its contents are not a valid index.
+    Corrupted { path: PathBuf, reason: String },
+    /// The operation does not agree with the declared fields.
+    Schema(String),
+    /// The value of a unique field is already used by the row.
+    ConstraintViolation { field: FieldName, row: NonZeroU32 },
+    /// The query text cannot be parsed. The position is the column of the offending character, counted from 1.
+    Query { position: usize, reason: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Corrupted { path, reason } => {
+                write!(f, "corrupted file {}: {}", path.display(), reason)
+            }
+            Self::Schema(reason) => write!(f, "schema error: {}", reason),
+            Self::ConstraintViolation { field, row } => {
+                write!(f, "value of unique field {} is used by row {}", field, row)
+            }
+            Self::Query { position, reason } => {
+                write!(f, "invalid query at column {}: {}", position, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
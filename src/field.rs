@@ -1,9 +1,20 @@
-use std::{fs, num::NonZeroU32, sync::Arc};
+use std::{
+    fs,
+    mem::size_of,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use hashbrown::HashMap;
 use idx_binary::{AvltrieeSearch, IdxBinary};
+use idx_file::AvltrieeNode;
+use various_data_file::DataAddress;
 
-use crate::Data;
+use crate::{
+    check_file, check_rows_count, has_node, manifest::FieldChange, Data, Error, FieldSchema,
+    FieldType, FieldValue, Manifest, Result,
+};
 
 pub type Field = IdxBinary;
 
@@ -20,26 +31,190 @@ impl Data {
     }
 
     /// Returns the value of the field with the specified name in the specified row as a number.
+    /// Values of numeric fields are decoded without parsing text.
     pub fn field_num(&self, row: NonZeroU32, name: &FieldName) -> f64 {
+        self.field_value(row, name).map_or(0.0, |v| v.to_num())
+    }
+
+    /// Returns the value of the field with the specified name in the specified row decoded as the type of the field.
+    pub fn field_value(&self, row: NonZeroU32, name: &FieldName) -> Option<FieldValue> {
         self.fields
             .get(name)
             .and_then(|v| v.value(row))
-            .and_then(|v| unsafe { std::str::from_utf8_unchecked(v) }.parse().ok())
-            .unwrap_or(0.0)
+            .and_then(|v| FieldValue::from_bytes(self.field_type(name), v))
+    }
+
+    /// Returns the type of the field. Fields that were created without a type hold bytes.
+    pub fn field_type(&self, name: &FieldName) -> FieldType {
+        self.manifest
+            .get(name)
+            .map_or(FieldType::Bytes, |schema| schema.field_type)
     }
 
-    pub(crate) fn create_field(&mut self, name: &FieldName) {
+    /// Returns the manifest that records the schema of each field.
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    pub(crate) fn create_field(&mut self, name: &FieldName) -> Result<()> {
         if !self.fields.contains_key(name) {
-            let mut fields_dir = self.fields_dir.clone();
-            fields_dir.push(name.as_ref().to_string());
-            fs::create_dir_all(&fields_dir).unwrap();
-            let field = Field::new(fields_dir, self.option.allocation_lot);
+            self.add_field(name, FieldType::Bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Records the field in the manifest, then creates its directory.
+    fn add_field(&mut self, name: &FieldName, field_type: FieldType) -> Result<()> {
+        self.manifest.insert(
+            name.clone(),
+            FieldSchema {
+                field_type,
+                created: Self::now(),
+                unique: false,
+                full_text: false,
+                ngram: false,
+            },
+        )?;
+        let mut fields_dir = self.fields_dir.clone();
+        fields_dir.push(name.as_ref());
+        fs::create_dir_all(&fields_dir)?;
+        let field = open(fields_dir, self.option.allocation_lot)?;
 
-            self.fields.insert(name.clone(), field);
+        self.fields.insert(name.clone(), field);
+        Ok(())
+    }
+
+    /// Creates a field whose values are of the specified type.
+    /// Returns an error if the field already exists with another type.
+    pub fn create_typed_field(&mut self, name: &FieldName, field_type: FieldType) -> Result<()> {
+        if self.fields.contains_key(name) {
+            let current = self.field_type(name);
+            return if current == field_type {
+                Ok(())
+            } else {
+                Err(Error::Schema(format!(
+                    "field {} already exists as {}",
+                    name, current
+                )))
+            };
+        }
+        self.add_field(name, field_type)
+    }
+
+    /// Returns an error if a value is not of the type of its field.
+    pub(crate) fn check_field_types(&self, fields: &HashMap<FieldName, Vec<u8>>) -> Result<()> {
+        for (name, value) in fields {
+            let field_type = self.field_type(name);
+            if !field_type.is_valid(value) {
+                return Err(Error::Schema(format!(
+                    "value of field {} is not {}",
+                    name, field_type
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the field and deletes its files.
+    pub fn drop_field(&mut self, name: &FieldName) -> Result<()> {
+        if !self.fields.contains_key(name) {
+            return Err(Error::Schema(format!("field {} does not exist", name)));
         }
+        self.manifest.begin_drop(name)?;
+        self.fields.remove(name);
+        self.full_text.remove(name);
+        self.ngram.remove(name);
+        change_dir(&self.fields_dir, &FieldChange::Drop(name.clone()))?;
+        self.manifest.finish_change()
+    }
+
+    /// Renames the field. Returns an error if a field with the new name already exists.
+    pub fn rename_field(&mut self, from: &FieldName, to: &FieldName) -> Result<()> {
+        if self.fields.contains_key(to) {
+            return Err(Error::Schema(format!("field {} already exists", to)));
+        }
+        if !self.fields.contains_key(from) {
+            return Err(Error::Schema(format!("field {} does not exist", from)));
+        }
+        self.manifest.begin_rename(from, to)?;
+        self.fields.remove(from);
+        let full_text = self.full_text.remove(from);
+        let ngram = self.ngram.remove(from);
+        change_dir(
+            &self.fields_dir,
+            &FieldChange::Rename(from.clone(), to.clone()),
+        )?;
+        self.manifest.finish_change()?;
+        let field = open(
+            self.fields_dir.join(to.as_str()),
+            self.option.allocation_lot,
+        )?;
+        self.fields.insert(to.clone(), field);
+        if let Some(index) = full_text {
+            self.full_text.insert(to.clone(), index);
+        }
+        if let Some(index) = ngram {
+            self.ngram.insert(to.clone(), index);
+        }
+        Ok(())
     }
 
     pub fn fields(&self) -> &Fields {
         &self.fields
     }
 }
+
+/// Directory a field is rebuilt in before it replaces the field directory.
+pub(crate) fn rebuild_dir(fields_dir: &Path) -> PathBuf {
+    fields_dir.with_file_name("rebuild")
+}
+
+/// Drops, renames or replaces the field directory. Does nothing if the change has already been made.
+pub(crate) fn change_dir(fields_dir: &Path, change: &FieldChange) -> Result<()> {
+    match change {
+        FieldChange::Drop(name) => {
+            let path = fields_dir.join(name.as_str());
+            if path.exists() {
+                fs::remove_dir_all(path)?;
+            }
+        }
+        FieldChange::Rename(from, to) => {
+            let from = fields_dir.join(from.as_str());
+            let to = fields_dir.join(to.as_str());
+            if from.exists() && !to.exists() {
+                fs::rename(from, to)?;
+            }
+        }
+        FieldChange::Replace(name) => {
+            let rebuilt = rebuild_dir(fields_dir);
+            if rebuilt.exists() {
+                let path = fields_dir.join(name.as_str());
+                if path.exists() {
+                    fs::remove_dir_all(&path)?;
+                }
+                fs::rename(rebuilt, path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the value of the row in the field stored in the directory, or None if the row has none.
+/// A row past the end of the index file has never been written to the field, and is not read.
+pub(crate) fn stored_value<'a>(field: &'a Field, dir: &Path, row: NonZeroU32) -> Option<&'a [u8]> {
+    has_node::<DataAddress>(&dir.join(".i"), row)
+        .then(|| field.value(row))
+        .flatten()
+}
+
+/// Opens the field stored in the directory after checking its files.
+pub(crate) fn open(dir: PathBuf, allocation_lot: u32) -> Result<Field> {
+    let unit = size_of::<AvltrieeNode<DataAddress>>() as u64;
+    let index_path = dir.join(".i");
+    let len = check_file(&index_path, unit)?;
+    check_file(&dir.join(".d"), 1)?;
+    check_file(&dir.join(".d.f"), 1)?;
+    let field = Field::new(dir, allocation_lot);
+    check_rows_count(&index_path, len, unit, field.as_ref().rows_count())?;
+    Ok(field)
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+
+/// The type of the values of a field.
+/// Values of numeric, boolean and timestamp fields are stored with an encoding that keeps them in numerical order in the index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    #[default]
+    Bytes,
+    String,
+    I64,
+    U64,
+    F64,
+    Bool,
+    /// Seconds since the UNIX epoch.
+    Timestamp,
+}
+
+impl FieldType {
+    /// Returns the name of the type as stored on disk.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bytes => "bytes",
+            Self::String => "string",
+            Self::I64 => "i64",
+            Self::U64 => "u64",
+            Self::F64 => "f64",
+            Self::Bool => "bool",
+            Self::Timestamp => "timestamp",
+        }
+    }
+
+    /// Returns the type with the specified name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "bytes" => Self::Bytes,
+            "string" => Self::String,
+            "i64" => Self::I64,
+            "u64" => Self::U64,
+            "f64" => Self::F64,
+            "bool" => Self::Bool,
+            "timestamp" => Self::Timestamp,
+            _ => return None,
+        })
+    }
+
+    /// Returns true if the bytes are a value of this type as encoded by [FieldValue].
+    pub fn is_valid(&self, bytes: &[u8]) -> bool {
+        match self {
+            Self::Bytes => true,
+            Self::String => std::str::from_utf8(bytes).is_ok(),
+            Self::I64 | Self::U64 | Self::F64 | Self::Timestamp => {
+                decode_nibbles::<8>(bytes).is_some()
+            }
+            Self::Bool => matches!(decode_nibbles::<1>(bytes), Some([0 | 1])),
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A typed value of a field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    Bytes(Vec<u8>),
+    String(String),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+    Timestamp(u64),
+}
+
+impl FieldValue {
+    /// Returns the type of the value.
+    pub fn field_type(&self) -> FieldType {
+        match self {
+            Self::Bytes(_) => FieldType::Bytes,
+            Self::String(_) => FieldType::String,
+            Self::I64(_) => FieldType::I64,
+            Self::U64(_) => FieldType::U64,
+            Self::F64(_) => FieldType::F64,
+            Self::Bool(_) => FieldType::Bool,
+            Self::Timestamp(_) => FieldType::Timestamp,
+        }
+    }
+
+    /// Returns the bytes stored in the field.
+    /// Fixed size values are written as big endian with each nibble mapped to a letter from `A` to `P`,
+    /// because the index compares runs of ASCII digits as numbers.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Bytes(v) => v.clone(),
+            Self::String(v) => v.as_bytes().to_vec(),
+            Self::I64(v) => encode_nibbles(&((*v as u64) ^ (1 << 63)).to_be_bytes()),
+            Self::U64(v) | Self::Timestamp(v) => encode_nibbles(&v.to_be_bytes()),
+            Self::F64(v) => {
+                let bits = v.to_bits();
+                let bits = if bits >> 63 == 1 {
+                    !bits
+                } else {
+                    bits ^ (1 << 63)
+                };
+                encode_nibbles(&bits.to_be_bytes())
+            }
+            Self::Bool(v) => encode_nibbles(&[*v as u8]),
+        }
+    }
+
+    /// Decodes bytes stored in a field of the specified type. Returns None if they are not a value of the type.
+    pub fn from_bytes(field_type: FieldType, bytes: &[u8]) -> Option<Self> {
+        Some(match field_type {
+            FieldType::Bytes => Self::Bytes(bytes.to_vec()),
+            FieldType::String => Self::String(String::from_utf8(bytes.to_vec()).ok()?),
+            FieldType::I64 => {
+                Self::I64((u64::from_be_bytes(decode_nibbles(bytes)?) ^ (1 << 63)) as i64)
+            }
+            FieldType::U64 => Self::U64(u64::from_be_bytes(decode_nibbles(bytes)?)),
+            FieldType::Timestamp => Self::Timestamp(u64::from_be_bytes(decode_nibbles(bytes)?)),
+            FieldType::F64 => {
+                let bits = u64::from_be_bytes(decode_nibbles(bytes)?);
+                Self::F64(f64::from_bits(if bits >> 63 == 1 {
+                    bits ^ (1 << 63)
+                } else {
+                    !bits
+                }))
+            }
+            FieldType::Bool => match decode_nibbles::<1>(bytes)? {
+                [0] => Self::Bool(false),
+                [1] => Self::Bool(true),
+                _ => return None,
+            },
+        })
+    }
+
+    /// Returns the value as a number. Text is parsed, and true is 1.
+    pub fn to_num(&self) -> f64 {
+        match self {
+            Self::Bytes(v) => std::str::from_utf8(v)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.0),
+            Self::String(v) => v.parse().unwrap_or(0.0),
+            Self::I64(v) => *v as f64,
+            Self::U64(v) | Self::Timestamp(v) => *v as f64,
+            Self::F64(v) => *v,
+            Self::Bool(v) => *v as u8 as f64,
+        }
+    }
+}
+
+impl From<FieldValue> for Vec<u8> {
+    fn from(value: FieldValue) -> Self {
+        value.to_bytes()
+    }
+}
+
+fn encode_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|b| [b'A' + (b >> 4), b'A' + (b & 0xf)])
+        .collect()
+}
+
+fn decode_nibbles<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+    if bytes.len() != N * 2 {
+        return None;
+    }
+    let mut decoded = [0; N];
+    for (d, pair) in decoded.iter_mut().zip(bytes.chunks(2)) {
+        let hi = pair[0].checked_sub(b'A').filter(|v| *v < 16)?;
+        let lo = pair[1].checked_sub(b'A').filter(|v| *v < 16)?;
+        *d = hi << 4 | lo;
+    }
+    Some(decoded)
+}
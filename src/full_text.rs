@@ -0,0 +1,191 @@
+use std::{collections::BTreeMap, num::NonZeroU32, ops::Bound};
+
+use hashbrown::HashMap;
+use idx_binary::AvltrieeSearch;
+
+use crate::{search, Data, Error, Field, FieldName, FieldType, Result, RowSet};
+
+/// Splits the text into lowercase words. A word is a run of letters and digits.
+pub(crate) fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// An inverted index from the words in the values of a field to the rows and positions they appear at.
+/// It is not written to disk: it holds an entry for every word occurrence in memory and is built again each time the data is opened.
+#[derive(Debug, Default)]
+pub(crate) struct FullTextIndex {
+    postings: BTreeMap<String, HashMap<NonZeroU32, Vec<u32>>>,
+    /// Number of words in each row that has any.
+    lengths: HashMap<NonZeroU32, usize>,
+    /// Number of words in all rows.
+    total: usize,
+}
+
+impl FullTextIndex {
+    /// Indexes the values of the rows.
+    pub(crate) fn build<'a>(values: impl Iterator<Item = (NonZeroU32, &'a [u8])>) -> Self {
+        let mut index = Self::default();
+        for (row, value) in values {
+            index.insert(row, value);
+        }
+        index
+    }
+
+    /// Adds the words of the value of the row.
+    pub(crate) fn insert(&mut self, row: NonZeroU32, value: &[u8]) {
+        let words: Vec<String> = words(&String::from_utf8_lossy(value)).collect();
+        if words.is_empty() {
+            return;
+        }
+        self.total += words.len();
+        self.lengths.insert(row, words.len());
+        for (position, word) in words.into_iter().enumerate() {
+            self.postings
+                .entry(word)
+                .or_default()
+                .entry(row)
+                .or_default()
+                .push(position as u32);
+        }
+    }
+
+    /// Removes the words of the value the row had.
+    pub(crate) fn remove(&mut self, row: NonZeroU32, value: &[u8]) {
+        let Some(length) = self.lengths.remove(&row) else {
+            return;
+        };
+        self.total -= length;
+        for word in words(&String::from_utf8_lossy(value)) {
+            if let Some(rows) = self.postings.get_mut(&word) {
+                rows.remove(&row);
+                if rows.is_empty() {
+                    self.postings.remove(&word);
+                }
+            }
+        }
+    }
+
+    /// Rows matching the condition, or None if the condition is not a word search.
+    pub(crate) fn search(&self, condition: &search::Field) -> Option<RowSet> {
+        Some(match condition {
+            search::Field::Words(text) => self.words(&words(text).collect::<Vec<_>>()),
+            search::Field::Phrase(text) => self.phrase(&words(text).collect::<Vec<_>>()),
+            search::Field::WordForward(prefix) => self.word_forward(&prefix.to_lowercase()),
+            _ => return None,
+        })
+    }
+
+    /// Rows that have all the words. No words match no rows.
+    fn words(&self, words: &[String]) -> RowSet {
+        let mut postings = Vec::with_capacity(words.len());
+        for word in words {
+            let Some(rows) = self.postings.get(word) else {
+                return RowSet::new();
+            };
+            postings.push(rows);
+        }
+        postings.sort_by_key(|rows| rows.len());
+        let Some((first, others)) = postings.split_first() else {
+            return RowSet::new();
+        };
+        first
+            .keys()
+            .filter(|row| others.iter().all(|rows| rows.contains_key(*row)))
+            .collect()
+    }
+
+    /// Rows that have the words one after another.
+    fn phrase(&self, words: &[String]) -> RowSet {
+        let mut rows = self.words(words);
+        rows.retain(|row| {
+            let positions: Vec<_> = words.iter().map(|word| &self.postings[word][row]).collect();
+            positions[0].iter().any(|start| {
+                positions
+                    .iter()
+                    .zip(0..)
+                    .skip(1)
+                    .all(|(positions, i)| positions.binary_search(&(start + i)).is_ok())
+            })
+        });
+        rows
+    }
+
+    /// Rows that have a word starting with the prefix.
+    fn word_forward(&self, prefix: &str) -> RowSet {
+        self.postings
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(|(word, _)| word.starts_with(prefix))
+            .flat_map(|(_, rows)| rows.keys().copied())
+            .collect()
+    }
+
+    /// Scores how well the row matches the words with BM25.
+    pub(crate) fn score(&self, row: NonZeroU32, words: &[String]) -> f64 {
+        const K1: f64 = 1.2;
+        const B: f64 = 0.75;
+        let Some(row_length) = self.lengths.get(&row) else {
+            return 0.0;
+        };
+        let count = self.lengths.len() as f64;
+        let length = *row_length as f64 / (self.total as f64 / count);
+        words
+            .iter()
+            .filter_map(|word| {
+                let rows = self.postings.get(word)?;
+                let frequency = rows.get(&row)?.len() as f64;
+                let rarity =
+                    (1.0 + (count - rows.len() as f64 + 0.5) / (rows.len() as f64 + 0.5)).ln();
+                Some(rarity * frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length)))
+            })
+            .sum()
+    }
+}
+
+impl Data {
+    /// Keeps the words of the values of the field in a full-text index, so that word searches look them up instead of scanning every row.
+    /// The index is not persisted. It takes memory for every word occurrence in the field,
+    /// and every open reads all values of the field to build it again, including the open that [Data::restore] does to check the snapshot.
+    /// Returns an error if the field does not exist or does not hold text.
+    pub fn set_full_text(&mut self, name: &FieldName, full_text: bool) -> Result<()> {
+        let Some(mut schema) = self.manifest.get(name).cloned() else {
+            return Err(Error::Schema(format!("field {} does not exist", name)));
+        };
+        if full_text {
+            if !matches!(schema.field_type, FieldType::Bytes | FieldType::String) {
+                return Err(Error::Schema(format!(
+                    "field {} is {} and cannot have a full-text index",
+                    name, schema.field_type
+                )));
+            }
+            if !self.full_text.contains_key(name) {
+                let index = full_text_index(self.fields.get(name).unwrap());
+                self.full_text.insert(name.clone(), index);
+            }
+        } else {
+            self.full_text.remove(name);
+        }
+        schema.full_text = full_text;
+        self.manifest.insert(name.clone(), schema)
+    }
+
+    /// Rows of the field matching the word search, or None if the field has no full-text index or the condition is not a word search.
+    pub(crate) fn full_text_search(
+        &self,
+        name: &FieldName,
+        condition: &search::Field,
+    ) -> Option<RowSet> {
+        self.full_text.get(name)?.search(condition)
+    }
+}
+
+/// Indexes the words of every row of the field.
+pub(crate) fn full_text_index(field: &Field) -> FullTextIndex {
+    FullTextIndex::build(
+        field
+            .as_ref()
+            .iter()
+            .map(|row| (row, unsafe { field.value_unchecked(row) })),
+    )
+}
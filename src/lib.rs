@@ -1,33 +1,53 @@
 pub mod search;
 
+mod check;
+mod error;
 mod field;
+mod field_type;
+mod full_text;
+mod manifest;
+mod ngram;
 mod operation;
 mod option;
 mod row_fragment;
+mod row_set;
 mod serial;
+mod snapshot;
 mod sort;
+mod transaction;
+mod unique;
+mod wal;
 
+pub use check::CheckReport;
+pub use error::{Error, Result};
 pub use field::{Field, FieldName, Fields};
+pub use field_type::{FieldType, FieldValue};
 use idx_binary::AvltrieeSearch;
 pub use idx_binary::{self, AvltrieeIter, FileMmap, IdxBinary, IdxFile};
+pub use manifest::{FieldSchema, Manifest};
 pub use operation::*;
 pub use option::DataOption;
 pub use row_fragment::RowFragment;
-pub use search::{Condition, Search};
-pub use sort::{CustomOrderKey, CustomSort, Order, OrderKey};
+pub use row_set::RowSet;
+pub use search::{Condition, Cursor, OwnedCondition, Query, RowStream, Search};
+pub use sort::{CustomOrderKey, CustomSort, NoCustomSort, Order, OrderKey};
+pub use transaction::Transaction;
 pub use uuid::Uuid;
 
 use std::{
-    collections::BTreeSet,
     fs,
+    mem::size_of,
     num::NonZeroU32,
     path::{Path, PathBuf},
     time::{SystemTime, UNIX_EPOCH},
 };
 
+use full_text::FullTextIndex;
+use hashbrown::HashMap;
+use idx_file::AvltrieeNode;
+use ngram::NgramIndex;
 use serial::SerialNumber;
-
-pub type RowSet = BTreeSet<NonZeroU32>;
+use wal::Wal;
 
 pub fn uuid_string(uuid: u128) -> String {
     Uuid::from_u128(uuid).to_string()
@@ -43,31 +63,82 @@ pub struct Data {
     term_end: Option<IdxFile<u64>>,
     last_updated: Option<IdxFile<u64>>,
     fields: Fields,
+    full_text: HashMap<FieldName, FullTextIndex>,
+    ngram: HashMap<FieldName, NgramIndex>,
+    manifest: Manifest,
+    wal: Option<Wal>,
 }
 
 impl Data {
     /// Opens the file and creates the Data.
+    /// Panics if the directory cannot be opened. Use [Data::try_new] to handle the error.
     pub fn new<P: AsRef<Path>>(dir: P, option: DataOption) -> Self {
+        Self::try_new(dir, option).unwrap()
+    }
+
+    /// Opens the file and creates the Data. Returns an error if the directory or any index file cannot be opened or is corrupted.
+    pub fn try_new<P: AsRef<Path>>(dir: P, option: DataOption) -> Result<Self> {
         let dir = dir.as_ref();
         if !dir.exists() {
-            fs::create_dir_all(dir).unwrap();
+            fs::create_dir_all(dir)?;
         }
+        snapshot::finish_restore(dir)?;
 
         let mut fields = Fields::default();
+        let mut manifest = Manifest::read(dir)?;
 
         let mut fields_dir = dir.to_path_buf();
         fields_dir.push("fields");
+        if let Some(change) = manifest.change() {
+            field::change_dir(&fields_dir, change)?;
+            manifest.finish_change()?;
+        }
         if fields_dir.exists() {
-            for d in fields_dir.read_dir().unwrap().into_iter() {
-                let d = d.unwrap();
-                if d.file_type().unwrap().is_dir() {
+            for d in fields_dir.read_dir()? {
+                let d = d?;
+                if d.file_type()?.is_dir() {
                     if let Some(name) = d.file_name().to_str() {
-                        let field = Field::new(d.path(), option.allocation_lot);
-                        fields.insert(FieldName::new(name.into()), field);
+                        let name = FieldName::new(name.into());
+                        let field = field::open(d.path(), option.allocation_lot)?;
+                        if manifest.get(&name).is_none() {
+                            // Fields created before the manifest existed hold bytes.
+                            manifest.insert(
+                                name.clone(),
+                                FieldSchema {
+                                    field_type: FieldType::Bytes,
+                                    created: Self::now(),
+                                    unique: false,
+                                    full_text: false,
+                                    ngram: false,
+                                },
+                            )?;
+                        }
+                        fields.insert(name, field);
                     }
                 }
             }
         }
+        for name in manifest.fields().keys() {
+            if !fields.contains_key(name) {
+                // The process stopped after recording the field and before creating its directory.
+                let path = fields_dir.join(name.as_str());
+                fs::create_dir_all(&path)?;
+                fields.insert(name.clone(), field::open(path, option.allocation_lot)?);
+            }
+        }
+
+        let full_text = manifest
+            .fields()
+            .iter()
+            .filter(|(_, schema)| schema.full_text)
+            .map(|(name, _)| (name.clone(), full_text::full_text_index(&fields[name])))
+            .collect();
+        let ngram = manifest
+            .fields()
+            .iter()
+            .filter(|(_, schema)| schema.ngram)
+            .map(|(name, _)| (name.clone(), ngram::ngram_index(&fields[name])))
+            .collect();
 
         let serial = SerialNumber::new(
             {
@@ -76,59 +147,86 @@ impl Data {
                 path
             },
             option.allocation_lot,
-        );
-        let uuid = option.uuid.then(|| {
-            IdxFile::new(
-                {
-                    let mut path = dir.to_path_buf();
-                    path.push("uuid.i");
-                    path
-                },
-                option.allocation_lot,
-            )
-        });
-        let activity = option.activity.then(|| {
-            IdxFile::new(
-                {
-                    let mut path = dir.to_path_buf();
-                    path.push("activity.i");
-                    path
-                },
-                option.allocation_lot,
-            )
-        });
-        let term_begin = option.term.then(|| {
-            IdxFile::new(
-                {
-                    let mut path = dir.to_path_buf();
-                    path.push("term_begin.i");
-                    path
-                },
-                option.allocation_lot,
-            )
-        });
-        let term_end = option.term.then(|| {
-            IdxFile::new(
-                {
-                    let mut path = dir.to_path_buf();
-                    path.push("term_end.i");
-                    path
-                },
-                option.allocation_lot,
-            )
-        });
-        let last_updated = option.last_updated.then(|| {
-            IdxFile::new(
-                {
-                    let mut path = dir.to_path_buf();
-                    path.push("last_updated.i");
-                    path
-                },
-                option.allocation_lot,
-            )
-        });
+        )?;
+        let uuid = option
+            .uuid
+            .then(|| {
+                open_idx_file(
+                    {
+                        let mut path = dir.to_path_buf();
+                        path.push("uuid.i");
+                        path
+                    },
+                    option.allocation_lot,
+                )
+            })
+            .transpose()?;
+        let activity = option
+            .activity
+            .then(|| {
+                open_idx_file(
+                    {
+                        let mut path = dir.to_path_buf();
+                        path.push("activity.i");
+                        path
+                    },
+                    option.allocation_lot,
+                )
+            })
+            .transpose()?;
+        let term_begin = option
+            .term
+            .then(|| {
+                open_idx_file(
+                    {
+                        let mut path = dir.to_path_buf();
+                        path.push("term_begin.i");
+                        path
+                    },
+                    option.allocation_lot,
+                )
+            })
+            .transpose()?;
+        let term_end = option
+            .term
+            .then(|| {
+                open_idx_file(
+                    {
+                        let mut path = dir.to_path_buf();
+                        path.push("term_end.i");
+                        path
+                    },
+                    option.allocation_lot,
+                )
+            })
+            .transpose()?;
+        let last_updated = option
+            .last_updated
+            .then(|| {
+                open_idx_file(
+                    {
+                        let mut path = dir.to_path_buf();
+                        path.push("last_updated.i");
+                        path
+                    },
+                    option.allocation_lot,
+                )
+            })
+            .transpose()?;
+        let wal_path = {
+            let mut path = dir.to_path_buf();
+            path.push("wal");
+            path
+        };
+        // A log left by an earlier open with the option on is still replayed.
+        let (wal, pending) = if option.wal || wal_path.exists() {
+            let (wal, pending) = Wal::open(wal_path)?;
+            (Some(wal), pending)
+        } else {
+            (None, None)
+        };
 
-        Self {
+        let mut data = Self {
             fields_dir,
             option,
             serial,
@@ -138,7 +236,20 @@ impl Data {
             term_end,
             last_updated,
             fields,
+            full_text,
+            ngram,
+            manifest,
+            wal,
+        };
+        if let Some(operations) = pending {
+            data.redo(operations)?;
+            // The log holds the values written, not the index pages, so a tree left half-written by the crash is rebuilt.
+            data.repair()?;
+        }
+        if !data.option.wal {
+            data.wal = None;
         }
+        Ok(data)
     }
 
     /// Returns a serial number.The serial number is incremented each time data is added.
@@ -192,6 +303,11 @@ impl Data {
         self.serial.iter().collect()
     }
 
+    /// Returns the directory the data is stored in.
+    fn dir(&self) -> &Path {
+        self.fields_dir.parent().unwrap()
+    }
+
     fn now() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -199,3 +315,48 @@ impl Data {
             .as_secs()
     }
 }
+
+/// Checks that the file can be opened for writing and that its length is a whole number of units.
+fn check_file(path: &Path, unit: u64) -> Result<u64> {
+    let len = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?
+        .metadata()?
+        .len();
+    if len % unit != 0 {
+        return Err(Error::Corrupted {
+            path: path.to_path_buf(),
+            reason: format!("length {} is not a multiple of {}", len, unit),
+        });
+    }
+    Ok(len)
+}
+
+/// Checks that the rows count recorded in the header fits in the file.
+fn check_rows_count(path: &Path, len: u64, unit: u64, rows_count: u32) -> Result<()> {
+    if len > 0 && rows_count as u64 > len / unit - 1 {
+        return Err(Error::Corrupted {
+            path: path.to_path_buf(),
+            reason: format!("rows count {} exceeds file capacity", rows_count),
+        });
+    }
+    Ok(())
+}
+
+/// Returns true if the index file has room for the node of the row.
+/// Reading the node of a row past the file reads past the mapped memory, so rows that may never have been written are checked first.
+fn has_node<T>(path: &Path, row: NonZeroU32) -> bool {
+    fs::metadata(path)
+        .is_ok_and(|m| (row.get() as u64) < m.len() / size_of::<AvltrieeNode<T>>() as u64)
+}
+
+fn open_idx_file<T>(path: PathBuf, allocation_lot: u32) -> Result<IdxFile<T>> {
+    let unit = size_of::<AvltrieeNode<T>>() as u64;
+    let len = check_file(&path, unit)?;
+    let idx = IdxFile::new(&path, allocation_lot);
+    check_rows_count(&path, len, unit, idx.rows_count())?;
+    Ok(idx)
+}
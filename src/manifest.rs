@@ -0,0 +1,247 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{Error, FieldName, FieldType, Result};
+
+/// Name of the manifest file in the data directory.
+pub(crate) const MANIFEST_FILE: &str = "manifest";
+
+/// What the manifest records about a field.
+/// There is no indexed flag or collation: every field is kept in an AVL tree index ordered by its type's natural order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldSchema {
+    pub field_type: FieldType,
+    /// Seconds since the UNIX epoch when the field was created.
+    pub created: u64,
+    /// No two rows may have the same value.
+    pub unique: bool,
+    /// The words of the values are kept in a full-text index.
+    pub full_text: bool,
+    /// Runs of characters of the values are kept in an n-gram index.
+    pub ngram: bool,
+}
+
+/// A change to the field directories that has been recorded in the manifest but may not have been made yet.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum FieldChange {
+    Drop(FieldName),
+    Rename(FieldName, FieldName),
+    /// The rebuilt directory of the field replaces the old one.
+    Replace(FieldName),
+}
+
+/// The fields of a data directory.
+///
+/// Stored as one line per field: the name, the type, the creation time and the options separated by tabs.
+/// Tabs, line feeds and backslashes in names are escaped with a backslash.
+/// A line with an empty name records a field directory being dropped, renamed or replaced.
+#[derive(Debug)]
+pub struct Manifest {
+    path: PathBuf,
+    fields: BTreeMap<FieldName, FieldSchema>,
+    change: Option<FieldChange>,
+}
+
+impl Manifest {
+    /// Reads the manifest of the data directory. Returns an empty manifest if the directory has none.
+    pub fn read<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let path = dir.as_ref().join(MANIFEST_FILE);
+        let mut fields = BTreeMap::new();
+        let mut change = None;
+        if path.exists() {
+            let text = fs::read_to_string(&path)?;
+            for line in text.lines().filter(|line| !line.is_empty()) {
+                let corrupted = |reason: &str| Error::Corrupted {
+                    path: path.clone(),
+                    reason: format!("{}: {}", reason, line),
+                };
+                let mut columns = line.split('\t');
+                if line.starts_with('\t') {
+                    columns.next();
+                    let mut name = || {
+                        columns
+                            .next()
+                            .and_then(unescape)
+                            .map(FieldName::new)
+                            .ok_or_else(|| corrupted("invalid name"))
+                    };
+                    change = Some(match name()?.as_str() {
+                        "drop" => FieldChange::Drop(name()?),
+                        "rename" => FieldChange::Rename(name()?, name()?),
+                        "replace" => FieldChange::Replace(name()?),
+                        _ => return Err(corrupted("invalid change")),
+                    });
+                    continue;
+                }
+                let name = columns
+                    .next()
+                    .and_then(unescape)
+                    .ok_or_else(|| corrupted("invalid name"))?;
+                let field_type = columns
+                    .next()
+                    .and_then(FieldType::from_name)
+                    .ok_or_else(|| corrupted("invalid type"))?;
+                let created = columns
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| corrupted("invalid creation time"))?;
+                let mut unique = false;
+                let mut full_text = false;
+                let mut ngram = false;
+                for option in columns {
+                    match option {
+                        "unique" => unique = true,
+                        "full_text" => full_text = true,
+                        "ngram" => ngram = true,
+                        _ => return Err(corrupted("invalid option")),
+                    }
+                }
+                fields.insert(
+                    FieldName::new(name),
+                    FieldSchema {
+                        field_type,
+                        created,
+                        unique,
+                        full_text,
+                        ngram,
+                    },
+                );
+            }
+        }
+        Ok(Self {
+            path,
+            fields,
+            change,
+        })
+    }
+
+    /// Returns the fields by name.
+    pub fn fields(&self) -> &BTreeMap<FieldName, FieldSchema> {
+        &self.fields
+    }
+
+    /// Returns the schema of the field.
+    pub fn get(&self, name: &FieldName) -> Option<&FieldSchema> {
+        self.fields.get(name)
+    }
+
+    pub(crate) fn insert(&mut self, name: FieldName, schema: FieldSchema) -> Result<()> {
+        self.fields.insert(name, schema);
+        self.write()
+    }
+
+    /// Returns the change left unfinished by a process that stopped while making it.
+    pub(crate) fn change(&self) -> Option<&FieldChange> {
+        self.change.as_ref()
+    }
+
+    /// Removes the field and records that its directory is being dropped.
+    /// Leaves the manifest as it was if it cannot be written.
+    pub(crate) fn begin_drop(&mut self, name: &FieldName) -> Result<()> {
+        let schema = self.fields.remove(name);
+        self.change = Some(FieldChange::Drop(name.clone()));
+        self.write().inspect_err(|_| {
+            self.change = None;
+            if let Some(schema) = schema {
+                self.fields.insert(name.clone(), schema);
+            }
+        })
+    }
+
+    /// Renames the field and records that its directory is being renamed.
+    /// Leaves the manifest as it was if it cannot be written.
+    pub(crate) fn begin_rename(&mut self, from: &FieldName, to: &FieldName) -> Result<()> {
+        if let Some(schema) = self.fields.remove(from) {
+            self.fields.insert(to.clone(), schema);
+        }
+        self.change = Some(FieldChange::Rename(from.clone(), to.clone()));
+        self.write().inspect_err(|_| {
+            self.change = None;
+            if let Some(schema) = self.fields.remove(to) {
+                self.fields.insert(from.clone(), schema);
+            }
+        })
+    }
+
+    /// Records that the directory of the field is being replaced by its rebuilt directory.
+    pub(crate) fn begin_replace(&mut self, name: &FieldName) -> Result<()> {
+        self.change = Some(FieldChange::Replace(name.clone()));
+        self.write()
+    }
+
+    /// Records that the change of the field directories has been made.
+    pub(crate) fn finish_change(&mut self) -> Result<()> {
+        self.change = None;
+        self.write()
+    }
+
+    /// Writes the manifest to a temporary file and renames it over the old one, so that readers always see a whole manifest.
+    fn write(&self) -> Result<()> {
+        let mut text = String::new();
+        for (name, schema) in &self.fields {
+            text += &format!(
+                "{}\t{}\t{}",
+                escape(name),
+                schema.field_type.name(),
+                schema.created
+            );
+            if schema.unique {
+                text += "\tunique";
+            }
+            if schema.full_text {
+                text += "\tfull_text";
+            }
+            if schema.ngram {
+                text += "\tngram";
+            }
+            text.push('\n');
+        }
+        match &self.change {
+            Some(FieldChange::Drop(name)) => text += &format!("\tdrop\t{}\n", escape(name)),
+            Some(FieldChange::Rename(from, to)) => {
+                text += &format!("\trename\t{}\t{}\n", escape(from), escape(to))
+            }
+            Some(FieldChange::Replace(name)) => text += &format!("\treplace\t{}\n", escape(name)),
+            None => {}
+        }
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '\\' => escaped += "\\\\",
+            '\t' => escaped += "\\t",
+            '\n' => escaped += "\\n",
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(name: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' {
+            match chars.next()? {
+                '\\' => '\\',
+                't' => '\t',
+                'n' => '\n',
+                _ => return None,
+            }
+        } else {
+            c
+        });
+    }
+    Some(unescaped)
+}
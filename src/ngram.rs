@@ -0,0 +1,133 @@
+use std::num::NonZeroU32;
+
+use hashbrown::HashMap;
+use idx_binary::AvltrieeSearch;
+
+use crate::{search, Data, Error, Field, FieldName, FieldType, Result, RowSet};
+
+/// Longest run of characters indexed.
+const N: usize = 3;
+
+/// Runs of n characters in the text.
+fn grams(chars: &[char], n: usize) -> impl Iterator<Item = String> + '_ {
+    chars.windows(n).map(|gram| gram.iter().collect())
+}
+
+/// An index from every run of one to [N] characters in the values of a field to the rows that have it.
+/// Substring searches look up the rows that have every longest run of the text, so that only those rows are checked.
+/// Text of two characters, which Japanese words often are, is looked up by its bigram.
+/// It is not written to disk: each row is held under up to three grams per character of its value, and the index is built again each time the data is opened.
+#[derive(Debug, Default)]
+pub(crate) struct NgramIndex {
+    grams: HashMap<String, RowSet>,
+}
+
+impl NgramIndex {
+    /// Indexes the values of the rows.
+    pub(crate) fn build<'a>(values: impl Iterator<Item = (NonZeroU32, &'a [u8])>) -> Self {
+        let mut index = Self::default();
+        for (row, value) in values {
+            index.insert(row, value);
+        }
+        index
+    }
+
+    /// Adds the grams of the value of the row.
+    pub(crate) fn insert(&mut self, row: NonZeroU32, value: &[u8]) {
+        let chars: Vec<char> = String::from_utf8_lossy(value).chars().collect();
+        for n in 1..=N {
+            for gram in grams(&chars, n) {
+                self.grams.entry(gram).or_default().insert(row);
+            }
+        }
+    }
+
+    /// Removes the grams of the value the row had.
+    pub(crate) fn remove(&mut self, row: NonZeroU32, value: &[u8]) {
+        let chars: Vec<char> = String::from_utf8_lossy(value).chars().collect();
+        for n in 1..=N {
+            for gram in grams(&chars, n) {
+                if let Some(rows) = self.grams.get_mut(&gram) {
+                    rows.remove(&row);
+                    if rows.is_empty() {
+                        self.grams.remove(&gram);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rows that have every longest gram of the text. None for empty text, which every row has.
+    fn candidates(&self, text: &str) -> Option<RowSet> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut candidates: Option<RowSet> = None;
+        for gram in grams(&chars, chars.len().min(N)) {
+            let Some(rows) = self.grams.get(&gram) else {
+                return Some(RowSet::new());
+            };
+            match candidates {
+                Some(ref mut candidates) => *candidates &= rows,
+                None => candidates = Some(rows.clone()),
+            }
+        }
+        candidates
+    }
+}
+
+impl Data {
+    /// Keeps every run of one to three characters of the values of the field in an n-gram index,
+    /// so that [Forward](search::Field::Forward), [Partial](search::Field::Partial) and [Backward](search::Field::Backward)
+    /// searches only check the rows that have the runs of the text instead of every row.
+    /// The index is not persisted. It takes memory for up to three grams per character of every value,
+    /// and every open reads all values of the field to build it again, including the open that [Data::restore] does to check the snapshot.
+    /// Returns an error if the field does not exist or does not hold text.
+    pub fn set_ngram(&mut self, name: &FieldName, ngram: bool) -> Result<()> {
+        let Some(mut schema) = self.manifest.get(name).cloned() else {
+            return Err(Error::Schema(format!("field {} does not exist", name)));
+        };
+        if ngram {
+            if !matches!(schema.field_type, FieldType::Bytes | FieldType::String) {
+                return Err(Error::Schema(format!(
+                    "field {} is {} and cannot have an n-gram index",
+                    name, schema.field_type
+                )));
+            }
+            if !self.ngram.contains_key(name) {
+                let index = ngram_index(self.fields.get(name).unwrap());
+                self.ngram.insert(name.clone(), index);
+            }
+        } else {
+            self.ngram.remove(name);
+        }
+        schema.ngram = ngram;
+        self.manifest.insert(name.clone(), schema)
+    }
+
+    /// Rows of the field matching the substring search, or None if the field has no n-gram index or the condition cannot use it.
+    pub(crate) fn ngram_search(
+        &self,
+        name: &FieldName,
+        condition: &search::Field,
+    ) -> Option<RowSet> {
+        let (search::Field::Forward(text)
+        | search::Field::Partial(text)
+        | search::Field::Backward(text)) = condition
+        else {
+            return None;
+        };
+        let field = self.fields.get(name)?;
+        let mut rows = self.ngram.get(name)?.candidates(text)?;
+        rows.retain(|row| Self::matches_field(*row, field, condition));
+        Some(rows)
+    }
+}
+
+/// Indexes the grams of every row of the field.
+pub(crate) fn ngram_index(field: &Field) -> NgramIndex {
+    NgramIndex::build(
+        field
+            .as_ref()
+            .iter()
+            .map(|row| (row, unsafe { field.value_unchecked(row) })),
+    )
+}
@@ -1,40 +1,39 @@
 use std::num::NonZeroU32;
 
-use futures::FutureExt;
 use hashbrown::HashMap;
 use idx_binary::AvltrieeUpdate;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{Data, FieldName};
+use crate::{
+    field::stored_value,
+    search,
+    transaction::RowState,
+    wal::{Operation, RowImage},
+    Data, Error, FieldName, Result,
+};
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub enum Activity {
     Inactive = 0,
+    #[default]
     Active = 1,
 }
-impl Default for Activity {
-    fn default() -> Self {
-        Self::Active
-    }
-}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum Term {
+    #[default]
     Default,
     Overwrite(u64),
 }
-impl Default for Term {
-    fn default() -> Self {
-        Self::Default
-    }
-}
 
 pub fn create_uuid() -> u128 {
     Uuid::new_v4().as_u128()
 }
 
 impl Data {
-    /// Delete row.
+    /// Insert row.
+    /// Panics if a file cannot be written or a value is rejected by its field type or unique constraint. Use [Data::try_insert] to handle the error.
     pub async fn insert(
         &mut self,
         activity: Activity,
@@ -42,13 +41,28 @@ impl Data {
         term_end: Term,
         fields: HashMap<FieldName, Vec<u8>>,
     ) -> NonZeroU32 {
-        let row = self.serial.next_row();
-        self.update(row, activity, term_begin, term_end, fields)
-            .await;
-        row
+        self.try_insert(activity, term_begin, term_end, fields)
+            .await
+            .unwrap()
+    }
+
+    /// Insert row. Returns an error if the files of a new field cannot be created, the write-ahead log cannot be written or a value is rejected by its field type or unique constraint.
+    /// Running out of disk space while an index file grows still panics.
+    pub async fn try_insert(
+        &mut self,
+        activity: Activity,
+        term_begin: Term,
+        term_end: Term,
+        fields: HashMap<FieldName, Vec<u8>>,
+    ) -> Result<NonZeroU32> {
+        let (row, serial) = self.serial.reserve(1)[0];
+        let image = self.image(row, Some(serial), activity, term_begin, term_end, fields)?;
+        self.commit(&[Operation::Write { row, image }])?;
+        Ok(row)
     }
 
     /// Update row.
+    /// Panics if a file cannot be written or a value is rejected by its field type or unique constraint. Use [Data::try_update] to handle the error.
     pub async fn update(
         &mut self,
         row: NonZeroU32,
@@ -57,125 +71,297 @@ impl Data {
         term_end: Term,
         fields: HashMap<FieldName, Vec<u8>>,
     ) {
-        for (key, _) in &fields {
-            if !self.fields.contains_key(key) {
-                self.create_field(key);
-            }
+        self.try_update(row, activity, term_begin, term_end, fields)
+            .await
+            .unwrap()
+    }
+
+    /// Update row. Returns an error if the files of a new field cannot be created, the write-ahead log cannot be written or a value is rejected by its field type or unique constraint.
+    /// Running out of disk space while an index file grows still panics.
+    pub async fn try_update(
+        &mut self,
+        row: NonZeroU32,
+        activity: Activity,
+        term_begin: Term,
+        term_end: Term,
+        fields: HashMap<FieldName, Vec<u8>>,
+    ) -> Result<()> {
+        let image = self.image(row, None, activity, term_begin, term_end, fields)?;
+        self.commit(&[Operation::Write { row, image }])
+    }
+
+    /// Delete row.
+    /// Panics if a file cannot be written. Use [Data::try_delete] to handle the error.
+    pub async fn delete(&mut self, row: NonZeroU32) {
+        self.try_delete(row).await.unwrap()
+    }
+
+    /// Delete row. Returns an error if the write-ahead log cannot be written.
+    /// Running out of disk space while an index file grows still panics.
+    pub async fn try_delete(&mut self, row: NonZeroU32) -> Result<()> {
+        self.commit(&[Operation::Delete { row }])
+    }
+
+    /// Updates the row whose unique field has the key value, or inserts a row with the key value if there is none.
+    /// Returns the row and true if it was inserted.
+    pub async fn upsert(
+        &mut self,
+        key_field: &FieldName,
+        key_value: Vec<u8>,
+        activity: Activity,
+        term_begin: Term,
+        term_end: Term,
+        mut fields: HashMap<FieldName, Vec<u8>>,
+    ) -> Result<(NonZeroU32, bool)> {
+        if !self
+            .manifest
+            .get(key_field)
+            .is_some_and(|schema| schema.unique)
+        {
+            return Err(Error::Schema(format!("field {} is not unique", key_field)));
         }
-        futures::future::join_all([
-            async {
-                futures::future::join_all(self.fields.iter_mut().filter_map(|(name, field)| {
-                    fields
-                        .get(&name.clone())
-                        .map(|v| async { field.update(row, v) })
-                }))
-                .await;
-            }
-            .boxed_local(),
-            async {
-                if let Some(ref mut uuid) = self.uuid {
-                    if uuid.node(row).is_none() {
-                        uuid.update(row, &Uuid::new_v4().as_u128());
-                    }
-                }
-            }
-            .boxed_local(),
-            async {
-                if let Some(ref mut f) = self.last_updated {
-                    f.update(row, &Self::now());
+        let found = self
+            .result_field(key_field, &search::Field::Match(key_value.clone()))
+            .into_iter()
+            .next();
+        fields.insert(key_field.clone(), key_value);
+        if let Some(row) = found {
+            self.try_update(row, activity, term_begin, term_end, fields)
+                .await?;
+            Ok((row, false))
+        } else {
+            let row = self
+                .try_insert(activity, term_begin, term_end, fields)
+                .await?;
+            Ok((row, true))
+        }
+    }
+
+    /// Updates the row with the UUID, or inserts a row with the UUID if there is none.
+    /// Returns the row and true if it was inserted.
+    pub async fn upsert_uuid(
+        &mut self,
+        uuid: u128,
+        activity: Activity,
+        term_begin: Term,
+        term_end: Term,
+        fields: HashMap<FieldName, Vec<u8>>,
+    ) -> Result<(NonZeroU32, bool)> {
+        let Some(index) = self.uuid.as_ref() else {
+            return Err(Error::Schema("uuid is disabled".into()));
+        };
+        if let Some(row) = index.iter_by(&uuid).next() {
+            self.try_update(row, activity, term_begin, term_end, fields)
+                .await?;
+            Ok((row, false))
+        } else {
+            let (row, serial) = self.serial.reserve(1)[0];
+            let mut image =
+                self.image(row, Some(serial), activity, term_begin, term_end, fields)?;
+            image.uuid = Some(uuid);
+            self.commit(&[Operation::Write { row, image }])?;
+            Ok((row, true))
+        }
+    }
+
+    /// Resolves the values to write to the row. Fields that do not exist yet are created when the row is written.
+    pub(crate) fn image(
+        &self,
+        row: NonZeroU32,
+        serial: Option<u32>,
+        activity: Activity,
+        term_begin: Term,
+        term_end: Term,
+        fields: HashMap<FieldName, Vec<u8>>,
+    ) -> Result<RowImage> {
+        self.check_field_types(&fields)?;
+        let now = Self::now();
+        Ok(RowImage {
+            serial,
+            uuid: self
+                .uuid
+                .as_ref()
+                .is_some_and(|uuid| serial.is_some() || uuid.node(row).is_none())
+                .then(create_uuid),
+            activity: activity as u8,
+            term_begin: if let Term::Overwrite(term) = term_begin {
+                term
+            } else {
+                now
+            },
+            term_end: if let Term::Overwrite(term) = term_end {
+                term
+            } else {
+                0
+            },
+            last_updated: now,
+            fields,
+        })
+    }
+
+    /// Records the operations in the write-ahead log if it is enabled, then applies them.
+    /// If an operation fails, the operations already applied are undone and the log entry is discarded.
+    /// If undoing fails too, the entry stays in the log and is replayed before the next commit; without the log the error is returned.
+    pub(crate) fn commit(&mut self, operations: &[Operation]) -> Result<()> {
+        if let Some(Some(pending)) = self.wal.as_mut().map(|wal| wal.unapplied()).transpose()? {
+            self.redo(pending)?;
+        }
+        self.check_unique(operations)?;
+        if let Some(wal) = &mut self.wal {
+            wal.append(operations)?;
+        }
+        let mut undo = Vec::with_capacity(operations.len());
+        for operation in operations {
+            let (row, state) = match operation {
+                // The row of an insert has never been written, so there is nothing to read.
+                Operation::Write { row, image } if image.serial.is_some() => {
+                    (*row, RowState::default())
                 }
-            }
-            .boxed_local(),
-            async {
-                if let Some(ref mut f) = self.activity {
-                    f.update(row, &(activity as u8));
+                Operation::Write { row, .. } | Operation::Delete { row } => {
+                    (*row, self.row_state(*row))
                 }
-            }
-            .boxed_local(),
-            async {
-                if let Some(ref mut f) = self.term_begin {
-                    f.update(
-                        row,
-                        &if let Term::Overwrite(term) = term_begin {
-                            term
-                        } else {
-                            Self::now()
-                        },
-                    );
+            };
+            // An operation that fails has not written its row, so only the operations before it are undone.
+            if let Err(e) = self.apply(operation) {
+                for (row, state) in undo.into_iter().rev() {
+                    self.restore_row(row, state)?;
                 }
-            }
-            .boxed_local(),
-            async {
-                if let Some(ref mut f) = self.term_end {
-                    f.update(
-                        row,
-                        &if let Term::Overwrite(term) = term_end {
-                            term
-                        } else {
-                            0
-                        },
-                    );
+                if let Some(wal) = &mut self.wal {
+                    wal.clear()?;
                 }
+                return Err(e);
             }
-            .boxed_local(),
-        ])
-        .await;
+            undo.push((row, state));
+        }
+        if let Some(wal) = &mut self.wal {
+            wal.applied()?;
+        }
+        Ok(())
     }
 
-    /// Delete row.
-    pub async fn delete(&mut self, row: NonZeroU32) {
-        futures::future::join(
-            futures::future::join(async { self.serial.delete(row) }, async {
-                futures::future::join_all(self.fields.iter_mut().map(|(_name, v)| async {
-                    v.delete(row);
-                }))
-                .await
-            }),
-            async {
-                let mut futs = vec![];
-                if let Some(ref mut f) = self.uuid {
-                    futs.push(
-                        async {
-                            f.delete(row);
-                        }
-                        .boxed_local(),
-                    );
+    fn apply(&mut self, operation: &Operation) -> Result<()> {
+        match operation {
+            Operation::Write { row, image } => {
+                for key in image.fields.keys() {
+                    self.create_field(key)?;
                 }
-                if let Some(ref mut f) = self.activity {
-                    futs.push(
-                        async {
-                            f.delete(row);
-                        }
-                        .boxed_local(),
-                    );
+                if let Some(serial) = image.serial {
+                    self.serial.assign(*row, serial)?;
                 }
-                if let Some(ref mut f) = self.term_begin {
-                    futs.push(
-                        async {
-                            f.delete(row);
-                        }
-                        .boxed_local(),
-                    );
+                self.write_row(*row, image);
+            }
+            Operation::Delete { row } => {
+                // A row that does not exist, or has already been deleted, is left as it is.
+                if self.serial.delete(*row)? {
+                    self.delete_row(*row);
                 }
-                if let Some(ref mut f) = self.term_end {
-                    futs.push(
-                        async {
-                            f.delete(row);
-                        }
-                        .boxed_local(),
-                    );
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies operations left in the write-ahead log by a process that stopped while applying them.
+    pub(crate) fn redo(&mut self, operations: Vec<Operation>) -> Result<()> {
+        for operation in operations {
+            match operation {
+                Operation::Write { row, image } => {
+                    for key in image.fields.keys() {
+                        self.create_field(key)?;
+                    }
+                    if let Some(serial) = image.serial {
+                        self.serial.assign(row, serial)?;
+                    }
+                    self.write_row(row, &image);
                 }
-                if let Some(ref mut f) = self.last_updated {
-                    futs.push(
-                        async {
-                            f.delete(row);
-                        }
-                        .boxed_local(),
-                    );
+                Operation::Delete { row } => {
+                    self.serial.redo_delete(row)?;
+                    self.delete_row(row);
                 }
-                futures::future::join_all(futs).await;
-            },
-        )
-        .await;
+            }
+        }
+        if let Some(wal) = &mut self.wal {
+            wal.clear()?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the value of the row in the text indexes of the field.
+    /// Called before the field is written, so that the words and grams of the old value can be read from it and removed.
+    pub(crate) fn reindex_text(&mut self, name: &FieldName, row: NonZeroU32, value: Option<&[u8]>) {
+        let full_text = self.full_text.get_mut(name);
+        let ngram = self.ngram.get_mut(name);
+        if full_text.is_none() && ngram.is_none() {
+            return;
+        }
+        let old = self
+            .fields
+            .get(name)
+            .and_then(|field| stored_value(field, &self.fields_dir.join(name.as_str()), row));
+        if let Some(index) = full_text {
+            if let Some(old) = old {
+                index.remove(row, old);
+            }
+            if let Some(value) = value {
+                index.insert(row, value);
+            }
+        }
+        if let Some(index) = ngram {
+            if let Some(old) = old {
+                index.remove(row, old);
+            }
+            if let Some(value) = value {
+                index.insert(row, value);
+            }
+        }
+    }
+
+    fn write_row(&mut self, row: NonZeroU32, image: &RowImage) {
+        for (name, v) in &image.fields {
+            self.reindex_text(name, row, Some(v));
+            if let Some(field) = self.fields.get_mut(name) {
+                field.update(row, v);
+            }
+        }
+        if let Some(ref mut f) = self.uuid {
+            if let Some(uuid) = image.uuid {
+                f.update(row, &uuid);
+            }
+        }
+        if let Some(ref mut f) = self.last_updated {
+            f.update(row, &image.last_updated);
+        }
+        if let Some(ref mut f) = self.activity {
+            f.update(row, &image.activity);
+        }
+        if let Some(ref mut f) = self.term_begin {
+            f.update(row, &image.term_begin);
+        }
+        if let Some(ref mut f) = self.term_end {
+            f.update(row, &image.term_end);
+        }
+    }
+
+    fn delete_row(&mut self, row: NonZeroU32) {
+        for name in self.fields.keys().cloned().collect::<Vec<_>>() {
+            self.reindex_text(&name, row, None);
+        }
+        for field in self.fields.values_mut() {
+            field.delete(row);
+        }
+        if let Some(ref mut f) = self.uuid {
+            f.delete(row);
+        }
+        if let Some(ref mut f) = self.activity {
+            f.delete(row);
+        }
+        if let Some(ref mut f) = self.term_begin {
+            f.delete(row);
+        }
+        if let Some(ref mut f) = self.term_end {
+            f.delete(row);
+        }
+        if let Some(ref mut f) = self.last_updated {
+            f.delete(row);
+        }
     }
 }
@@ -7,6 +7,10 @@ pub struct DataOption {
     pub term: bool,
     pub last_updated: bool,
     pub allocation_lot: u32,
+    /// Records each write in a write-ahead log before applying it, so that a write the process stopped in the middle of is finished on the next open.
+    /// Off by default, because every write then also rewrites the log.
+    #[serde(default)]
+    pub wal: bool,
 }
 impl Default for DataOption {
     fn default() -> Self {
@@ -16,6 +20,7 @@ impl Default for DataOption {
             term: true,
             last_updated: true,
             allocation_lot: 1,
+            wal: false,
         }
     }
 }
@@ -1,4 +1,4 @@
-use std::{num::NonZeroU32, path::PathBuf};
+use std::{io, num::NonZeroU32, path::PathBuf};
 
 use super::FileMmap;
 
@@ -8,29 +8,80 @@ pub struct RowFragment {
     filemmap: FileMmap,
 }
 impl RowFragment {
-    pub fn new(path: PathBuf) -> Self {
-        let mut filemmap = FileMmap::new(path).unwrap();
+    pub fn new(path: PathBuf) -> io::Result<Self> {
+        let mut filemmap = FileMmap::new(path)?;
         if filemmap.len() == 0 {
-            filemmap.set_len(U32_SIZE as u64).unwrap();
+            filemmap.set_len(U32_SIZE as u64)?;
         }
-        Self { filemmap }
+        Ok(Self { filemmap })
     }
 
     fn blank_count(&self) -> u64 {
         self.filemmap.len() / U32_SIZE as u64 - 1
     }
 
-    pub fn insert_blank(&mut self, row: NonZeroU32) {
-        self.filemmap.append(&row.get().to_ne_bytes()).unwrap();
+    pub fn insert_blank(&mut self, row: NonZeroU32) -> io::Result<()> {
+        self.filemmap.append(&row.get().to_ne_bytes())?;
+        Ok(())
     }
 
-    pub fn pop(&mut self) -> Option<NonZeroU32> {
+    pub fn pop(&mut self) -> io::Result<Option<NonZeroU32>> {
         let count = self.blank_count();
-        (count > 0).then(|| {
+        if count > 0 {
             let last = unsafe { *(self.filemmap.as_ptr() as *mut u32).offset(count as isize) };
-            self.filemmap.set_len(count * U32_SIZE as u64).unwrap();
-            unsafe { NonZeroU32::new_unchecked(last) }
-        })
+            self.filemmap.set_len(count * U32_SIZE as u64)?;
+            Ok(Some(unsafe { NonZeroU32::new_unchecked(last) }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Removes the row from the blank list. Returns false if the row was not blank.
+    pub fn remove_blank(&mut self, row: NonZeroU32) -> io::Result<bool> {
+        let count = self.blank_count();
+        let list = self.filemmap.as_ptr() as *mut u32;
+        if let Some(i) = (1..=count).find(|i| unsafe { *list.offset(*i as isize) } == row.get()) {
+            let last = unsafe { *list.offset(count as isize) };
+            self.filemmap.set_len(count * U32_SIZE as u64)?;
+            if i != count {
+                unsafe { *(self.filemmap.as_ptr() as *mut u32).offset(i as isize) = last };
+            }
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Returns the blank rows in the order they were added.
+    pub fn blanks(&self) -> Vec<NonZeroU32> {
+        let list = self.filemmap.as_ptr() as *const u32;
+        (1..=self.blank_count())
+            .filter_map(|i| NonZeroU32::new(unsafe { *list.offset(i as isize) }))
+            .collect()
+    }
+
+    /// Replaces the blank list.
+    pub fn set_blanks(&mut self, rows: &[NonZeroU32]) -> io::Result<()> {
+        self.filemmap
+            .set_len(((rows.len() + 1) * U32_SIZE) as u64)?;
+        let list = self.filemmap.as_ptr() as *mut u32;
+        for (i, row) in rows.iter().enumerate() {
+            unsafe { *list.add(i + 1) = row.get() };
+        }
+        Ok(())
+    }
+
+    /// Returns the last serial number issued.
+    pub fn serial(&self) -> u32 {
+        unsafe { *(self.filemmap.as_ptr() as *const u32) }
+    }
+
+    /// Raises the last serial number issued to the specified value if it is lower.
+    pub fn serial_raise(&mut self, serial: u32) {
+        let current = unsafe { &mut *(self.filemmap.as_ptr() as *mut u32) };
+        if *current < serial {
+            *current = serial;
+        }
     }
 
     pub fn serial_increment(&mut self) -> NonZeroU32 {
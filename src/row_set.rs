@@ -0,0 +1,226 @@
+use std::{
+    fmt,
+    num::NonZeroU32,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, RangeBounds, Sub, SubAssign},
+};
+
+use roaring::RoaringBitmap;
+
+/// A set of rows stored as a compressed bitmap. Iterates in ascending row order.
+///
+/// `&`, `|` and `-` compute the intersection, union and difference of whole sets,
+/// which is much faster than checking the rows one by one.
+#[derive(Clone, Default, PartialEq)]
+pub struct RowSet(RoaringBitmap);
+
+impl RowSet {
+    /// Makes an empty set.
+    pub fn new() -> Self {
+        Self(RoaringBitmap::new())
+    }
+
+    /// Returns the number of rows in the set.
+    pub fn len(&self) -> usize {
+        self.0.len() as usize
+    }
+
+    /// Returns true if the set contains no rows.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns true if the set contains the row.
+    pub fn contains(&self, row: &NonZeroU32) -> bool {
+        self.0.contains(row.get())
+    }
+
+    /// Adds the row. Returns false if the set already contained it.
+    pub fn insert(&mut self, row: NonZeroU32) -> bool {
+        self.0.insert(row.get())
+    }
+
+    /// Removes the row. Returns false if the set did not contain it.
+    pub fn remove(&mut self, row: &NonZeroU32) -> bool {
+        self.0.remove(row.get())
+    }
+
+    /// Removes all rows.
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Returns the smallest row.
+    pub fn first(&self) -> Option<NonZeroU32> {
+        self.0.min().and_then(NonZeroU32::new)
+    }
+
+    /// Returns the largest row.
+    pub fn last(&self) -> Option<NonZeroU32> {
+        self.0.max().and_then(NonZeroU32::new)
+    }
+
+    /// Keeps only the rows for which the predicate returns true.
+    pub fn retain<F: FnMut(&NonZeroU32) -> bool>(&mut self, mut f: F) {
+        let removed: RoaringBitmap = self
+            .iter()
+            .filter(|row| !f(row))
+            .map(|row| row.get())
+            .collect();
+        self.0 -= removed;
+    }
+
+    /// Iterates over the rows in ascending order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.0.iter())
+    }
+
+    /// Iterates over the rows in the range in ascending order.
+    pub fn range<R: RangeBounds<u32>>(&self, range: R) -> Iter<'_> {
+        Iter(self.0.range(range))
+    }
+
+    /// Returns true if every row of the set is also in the other set.
+    pub fn is_subset(&self, other: &RowSet) -> bool {
+        self.0.is_subset(&other.0)
+    }
+
+    /// Returns true if the sets have no row in common.
+    pub fn is_disjoint(&self, other: &RowSet) -> bool {
+        self.0.is_disjoint(&other.0)
+    }
+}
+
+impl fmt::Debug for RowSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the rows of a [RowSet].
+pub struct Iter<'a>(roaring::bitmap::Iter<'a>);
+
+impl Iterator for Iter<'_> {
+    type Item = NonZeroU32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(to_row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(to_row)
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// Owning iterator over the rows of a [RowSet].
+pub struct IntoIter(roaring::bitmap::IntoIter);
+
+impl Iterator for IntoIter {
+    type Item = NonZeroU32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(to_row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for IntoIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(to_row)
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+/// Zero is never inserted, so every value in the bitmap is a row.
+fn to_row(value: u32) -> NonZeroU32 {
+    unsafe { NonZeroU32::new_unchecked(value) }
+}
+
+impl IntoIterator for RowSet {
+    type Item = NonZeroU32;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+impl<'a> IntoIterator for &'a RowSet {
+    type Item = NonZeroU32;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<NonZeroU32> for RowSet {
+    fn from_iter<T: IntoIterator<Item = NonZeroU32>>(iter: T) -> Self {
+        Self(iter.into_iter().map(|row| row.get()).collect())
+    }
+}
+
+impl<'a> FromIterator<&'a NonZeroU32> for RowSet {
+    fn from_iter<T: IntoIterator<Item = &'a NonZeroU32>>(iter: T) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+impl Extend<NonZeroU32> for RowSet {
+    fn extend<T: IntoIterator<Item = NonZeroU32>>(&mut self, iter: T) {
+        self.0.extend(iter.into_iter().map(|row| row.get()))
+    }
+}
+
+impl<const N: usize> From<[NonZeroU32; N]> for RowSet {
+    fn from(rows: [NonZeroU32; N]) -> Self {
+        rows.into_iter().collect()
+    }
+}
+
+macro_rules! set_op {
+    ($op:ident, $fn:ident, $op_assign:ident, $fn_assign:ident) => {
+        impl $op_assign<&RowSet> for RowSet {
+            fn $fn_assign(&mut self, rhs: &RowSet) {
+                self.0.$fn_assign(&rhs.0);
+            }
+        }
+
+        impl $op_assign for RowSet {
+            fn $fn_assign(&mut self, rhs: RowSet) {
+                self.0.$fn_assign(rhs.0);
+            }
+        }
+
+        impl $op<&RowSet> for &RowSet {
+            type Output = RowSet;
+
+            fn $fn(self, rhs: &RowSet) -> RowSet {
+                RowSet((&self.0).$fn(&rhs.0))
+            }
+        }
+
+        impl $op for RowSet {
+            type Output = RowSet;
+
+            fn $fn(self, rhs: RowSet) -> RowSet {
+                RowSet(self.0.$fn(rhs.0))
+            }
+        }
+    };
+}
+
+set_op!(BitAnd, bitand, BitAndAssign, bitand_assign);
+set_op!(BitOr, bitor, BitOrAssign, bitor_assign);
+set_op!(Sub, sub, SubAssign, sub_assign);
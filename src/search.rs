@@ -1,24 +1,59 @@
+mod aggregate;
+mod count;
 mod enums;
+mod explain;
+mod facet;
+mod owned;
+mod page;
+mod pattern;
+mod plan;
+mod predicate;
+mod query;
+mod rank;
 mod result;
+mod stream;
 
 use crate::FieldName;
 
 use super::{Activity, Data};
 
+pub use aggregate::Aggregate;
 pub use enums::*;
+pub use explain::{Access, Evaluation, Plan, Step};
+pub use owned::OwnedCondition;
+pub use page::Cursor;
+pub use pattern::Pattern;
+pub use query::Query;
+pub use stream::RowStream;
 
 pub struct Search<'a> {
     data: &'a Data,
     conditions: Vec<Condition<'a>>,
+    offset: usize,
+    limit: Option<usize>,
 }
 impl<'a> Search<'a> {
     fn new(data: &'a Data) -> Self {
         Search {
             data,
             conditions: Vec::new(),
+            offset: 0,
+            limit: None,
         }
     }
 
+    /// Skips the specified number of rows in sorted results.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns at most the specified number of rows in sorted results. Sorting stops once enough rows are found.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
     /// Searches for data whose term is greater than or equal to the current date and time and is active.
     pub fn search_default(mut self) -> Self {
         if self.data.term_begin.is_some() {
@@ -67,32 +102,32 @@ impl<'a> Search<'a> {
 
 impl Data {
     /// Create a new [Search] object.
-    pub fn begin_search(&self) -> Search {
+    pub fn begin_search(&self) -> Search<'_> {
         Search::new(self)
     }
 
     /// Create a [Search] object with the field search set.
-    pub fn search_field<'a>(&'a self, name: FieldName, condition: &'a Field) -> Search {
+    pub fn search_field<'a>(&'a self, name: FieldName, condition: &'a Field) -> Search<'a> {
         Search::new(self).search_field(name, condition)
     }
 
     /// Create a [Search] object with the activity search set.
-    pub fn search_activity<'a>(&'a self, condition: Activity) -> Search {
+    pub fn search_activity(&self, condition: Activity) -> Search<'_> {
         Search::new(self).search_activity(condition)
     }
 
     /// Create a [Search] object with the term search set.
-    pub fn search_term<'a>(&'a self, condition: Term) -> Search {
+    pub fn search_term(&self, condition: Term) -> Search<'_> {
         Search::new(self).search_term(condition)
     }
 
     /// Create a [Search] object with the row search set.
-    pub fn search_row<'a>(&'a self, condition: &'a Number) -> Search {
+    pub fn search_row<'a>(&'a self, condition: &'a Number) -> Search<'a> {
         Search::new(self).search_row(condition)
     }
 
     /// Creates a [Search] object with a default search set. Searches for data whose term is greater than or equal to the current date and time and is active.
-    pub fn search_default(&self) -> Search {
+    pub fn search_default(&self) -> Search<'_> {
         Search::new(self).search_default()
     }
 }
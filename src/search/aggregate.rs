@@ -0,0 +1,104 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use idx_binary::{AvltrieeSearch, IdxBinary};
+
+use crate::{FieldName, FieldValue, RowSet, Search};
+
+/// Values of a field aggregated over rows. Rows without a value in the field are not counted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Aggregate {
+    pub count: usize,
+    /// Sum of the values as numbers. See [FieldValue::to_num].
+    pub sum: f64,
+    /// Smallest value in the order of the field index.
+    pub min: Option<FieldValue>,
+    /// Largest value in the order of the field index.
+    pub max: Option<FieldValue>,
+}
+
+impl Aggregate {
+    /// Returns the average of the values as numbers, or None if there are no values.
+    pub fn avg(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+}
+
+impl<'a> Search<'a> {
+    /// Aggregates the values of the field over the matching rows.
+    pub async fn aggregate(&self, field: &FieldName) -> Aggregate {
+        let rows = self.result().await;
+        let mut aggregate = Aggregate::default();
+        for row in &rows {
+            if let Some(value) = self.data.field_value(row, field) {
+                aggregate.count += 1;
+                aggregate.sum += value.to_num();
+            }
+        }
+        aggregate.min = self.first_in_index(&rows, field, false);
+        aggregate.max = self.first_in_index(&rows, field, true);
+        aggregate
+    }
+
+    /// Aggregates the values of the field over the matching rows for each value of the group field.
+    /// Rows without a value in the group field are left out.
+    pub async fn aggregate_by(
+        &self,
+        field: &FieldName,
+        group: &FieldName,
+    ) -> BTreeMap<Vec<u8>, Aggregate> {
+        let mut groups = BTreeMap::<Vec<u8>, Aggregate>::new();
+        let (Some(index), Some(group_index)) =
+            (self.data.fields.get(field), self.data.fields.get(group))
+        else {
+            return groups;
+        };
+        let field_type = self.data.field_type(field);
+        let mut bounds = BTreeMap::<Vec<u8>, (&[u8], &[u8])>::new();
+        for row in &self.result().await {
+            let (Some(key), Some(bytes)) = (group_index.value(row), index.value(row)) else {
+                continue;
+            };
+            let Some(value) = FieldValue::from_bytes(field_type, bytes) else {
+                continue;
+            };
+            let aggregate = groups.entry(key.to_vec()).or_default();
+            aggregate.count += 1;
+            aggregate.sum += value.to_num();
+            let (min, max) = bounds.entry(key.to_vec()).or_insert((bytes, bytes));
+            if IdxBinary::cmp(bytes, min) == Ordering::Less {
+                *min = bytes;
+            }
+            if IdxBinary::cmp(bytes, max) == Ordering::Greater {
+                *max = bytes;
+            }
+        }
+        for (key, (min, max)) in bounds {
+            if let Some(aggregate) = groups.get_mut(&key) {
+                aggregate.min = FieldValue::from_bytes(field_type, min);
+                aggregate.max = FieldValue::from_bytes(field_type, max);
+            }
+        }
+        groups
+    }
+
+    /// Returns the smallest value of the field among the matching rows. Walks the field index from the smallest value.
+    pub async fn min(&self, field: &FieldName) -> Option<FieldValue> {
+        self.first_in_index(&self.result().await, field, false)
+    }
+
+    /// Returns the largest value of the field among the matching rows. Walks the field index from the largest value.
+    pub async fn max(&self, field: &FieldName) -> Option<FieldValue> {
+        self.first_in_index(&self.result().await, field, true)
+    }
+
+    /// Returns the value of the first of the rows found walking the field index, from the largest value if desc.
+    fn first_in_index(&self, rows: &RowSet, field: &FieldName, desc: bool) -> Option<FieldValue> {
+        let triee = self.data.fields.get(field)?.as_ref();
+        let row = if desc {
+            triee.desc_iter().find(|row| rows.contains(row))
+        } else {
+            triee.iter().find(|row| rows.contains(row))
+        }?;
+        self.data.field_value(row, field)
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{Condition, Search};
+
+use super::{Field, Term};
+
+impl<'a> Search<'a> {
+    /// Returns the number of matching rows.
+    /// A single condition that an index walk finds exactly is counted from the index without collecting the rows.
+    pub async fn count(&self) -> usize {
+        match self.conditions.as_slice() {
+            [] => self.data.serial.iter().count(),
+            [Condition::Term(Term::In(_))
+            | Condition::Field(_, Field::Regex(_))
+            | Condition::Narrow(_)
+            | Condition::Owned(_)] => self.result().await.len(),
+            [condition] => match self.data.index_iter(condition) {
+                Some(iter) => iter.count(),
+                None => self.result().await.len(),
+            },
+            _ => self.result().await.len(),
+        }
+    }
+
+    /// Returns true if any row matches. Stops at the first matching row.
+    pub async fn exists(&self) -> bool {
+        self.stream().next().is_some()
+    }
+}
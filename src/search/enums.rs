@@ -1,11 +1,14 @@
 use crate::{Activity, FieldName};
+use serde::{Deserialize, Serialize};
 use std::{
     ops::RangeInclusive,
     sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
 
-#[derive(Clone, Debug, PartialEq)]
+use super::{OwnedCondition, Pattern};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Term {
     In(u64),
     Past(u64),
@@ -23,7 +26,7 @@ impl Default for Term {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Number {
     Min(isize),
     Max(isize),
@@ -31,7 +34,7 @@ pub enum Number {
     In(Vec<isize>),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Field {
     Match(Vec<u8>),
     Range(Vec<u8>, Vec<u8>),
@@ -43,9 +46,17 @@ pub enum Field {
     ValueForward(Arc<String>),
     ValueBackward(Arc<String>),
     ValuePartial(Arc<String>),
+    /// Values that have all the words, in any order. Words are runs of letters and digits compared without case.
+    Words(Arc<String>),
+    /// Values that have the words one after another.
+    Phrase(Arc<String>),
+    /// Values that have a word starting with the text.
+    WordForward(Arc<String>),
+    /// Values that the regular expression matches.
+    Regex(Pattern),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Condition<'a> {
     Activity(Activity),
     Term(Term),
@@ -55,4 +66,8 @@ pub enum Condition<'a> {
     Field(FieldName, &'a Field),
     Narrow(&'a Vec<Condition<'a>>),
     Wide(&'a Vec<Condition<'a>>),
+    /// Rows that do not match the condition.
+    Not(&'a Condition<'a>),
+    /// A condition that owns its values.
+    Owned(&'a OwnedCondition),
 }
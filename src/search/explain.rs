@@ -0,0 +1,146 @@
+use std::{fmt, time::Duration};
+
+use crate::{Condition, Data, Search};
+
+use super::{Field, Number};
+
+/// How a condition finds rows in its index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Access {
+    /// Finds the rows with the values with AVL tree lookups.
+    Lookup,
+    /// Walks the index from one value to another.
+    RangeWalk,
+    /// Checks the value of every row.
+    FullScan,
+    /// Combines the rows of the inner conditions.
+    Combine,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Access::Lookup => "AVL lookup",
+            Access::RangeWalk => "range walk",
+            Access::FullScan => "full scan",
+            Access::Combine => "combine",
+        })
+    }
+}
+
+/// How the planner applied a condition to the rows found so far.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Evaluation {
+    /// Found the rows of the condition with its index and intersected them with the rows found so far.
+    Lookup,
+    /// Found the rows of the negated condition with its index and removed them from the rows found so far.
+    Subtract,
+    /// Checked the values of each row found so far, or of all rows if it came first.
+    Filter,
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Evaluation::Lookup => "lookup",
+            Evaluation::Subtract => "subtract",
+            Evaluation::Filter => "filter",
+        })
+    }
+}
+
+/// One condition of a search in the order the planner evaluated it.
+#[derive(Clone, Debug)]
+pub struct Step {
+    /// The condition, formatted with [Debug].
+    pub condition: String,
+    pub access: Access,
+    pub evaluation: Evaluation,
+    /// Rows the condition was estimated to match, counted up to a sample size. None if it has no index to estimate from.
+    pub estimate: Option<usize>,
+    /// Rows left after the step.
+    pub rows: usize,
+    pub time: Duration,
+}
+
+/// How a search was run. Printing it shows one line per step.
+#[derive(Clone, Debug)]
+pub struct Plan {
+    pub steps: Vec<Step>,
+    /// Rows found by the search.
+    pub rows: usize,
+    pub time: Duration,
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} rows in {:?}", self.rows, self.time)?;
+        for (i, step) in self.steps.iter().enumerate() {
+            write!(
+                f,
+                "{}. {} by {}: {}",
+                i + 1,
+                step.evaluation,
+                step.access,
+                step.condition
+            )?;
+            if let Some(estimate) = step.estimate {
+                write!(f, ", estimated {}", estimate)?;
+            }
+            writeln!(f, ", {} rows left in {:?}", step.rows, step.time)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Search<'a> {
+    /// Runs the search and returns how each condition was evaluated, with the estimated and actual row counts and the time spent.
+    pub async fn explain(&self) -> Plan {
+        let start = std::time::Instant::now();
+        let mut steps = Vec::new();
+        let rows = if self.conditions.is_empty() {
+            self.data.all()
+        } else {
+            self.data.execute(&self.conditions, Some(&mut steps)).await
+        };
+        Plan {
+            steps,
+            rows: rows.len(),
+            time: start.elapsed(),
+        }
+    }
+}
+
+impl Data {
+    /// Returns how the condition finds rows when it is looked up.
+    pub(super) fn access(&self, condition: &Condition) -> Access {
+        match condition {
+            Condition::Activity(_) | Condition::Uuid(_) => Access::Lookup,
+            Condition::Term(_) => Access::RangeWalk,
+            Condition::Row(Number::Range(_) | Number::In(_)) => Access::Lookup,
+            Condition::Row(_) => Access::FullScan,
+            Condition::LastUpdated(Number::In(_)) => Access::Lookup,
+            Condition::LastUpdated(_) => Access::RangeWalk,
+            Condition::Field(_, Field::Match(_)) => Access::Lookup,
+            Condition::Field(_, Field::Min(_) | Field::Max(_) | Field::Range(_, _)) => {
+                Access::RangeWalk
+            }
+            Condition::Field(_, Field::Regex(pattern)) if pattern.prefixes().is_some() => {
+                Access::RangeWalk
+            }
+            Condition::Field(name, Field::Words(_) | Field::Phrase(_) | Field::WordForward(_))
+                if self.full_text.contains_key(name) =>
+            {
+                Access::Lookup
+            }
+            Condition::Field(
+                name,
+                Field::Forward(text) | Field::Partial(text) | Field::Backward(text),
+            ) if !text.is_empty() && self.ngram.contains_key(name) => Access::Lookup,
+            Condition::Field(_, _) => Access::FullScan,
+            Condition::Narrow(_) | Condition::Wide(_) | Condition::Not(_) | Condition::Owned(_) => {
+                Access::Combine
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::num::NonZeroU32;
+
+use idx_binary::{AvltrieeIter, AvltrieeSearch};
+
+use crate::{Data, FieldName, Search};
+
+impl Data {
+    /// Returns each value of the field with the number of rows that have it, in the order of the field index.
+    /// Seeks from one value to the next in the index instead of reading the value of every row.
+    pub fn distinct(&self, name: &FieldName) -> Vec<(&[u8], usize)> {
+        self.facets_by(name, |_| true)
+    }
+
+    /// Returns each value of the field with the number of rows that have it and for which the filter returns true.
+    /// Values that no such row has are left out.
+    fn facets_by(
+        &self,
+        name: &FieldName,
+        filter: impl Fn(&NonZeroU32) -> bool,
+    ) -> Vec<(&[u8], usize)> {
+        let mut facets = Vec::new();
+        let Some(field) = self.fields.get(name) else {
+            return facets;
+        };
+        let mut next = field.as_ref().iter().next();
+        while let Some(row) = next {
+            let value = unsafe { field.value_unchecked(row) };
+            let count = AvltrieeIter::by(field, value)
+                .filter(|row| filter(row))
+                .count();
+            if count > 0 {
+                facets.push((value, count));
+            }
+            next = AvltrieeIter::over_asc(field, value).next();
+        }
+        facets
+    }
+}
+
+impl<'a> Search<'a> {
+    /// Returns each value of the field with the number of matching rows that have it, in the order of the field index.
+    /// Values that no matching row has are left out.
+    pub async fn facets(&self, name: &FieldName) -> Vec<(&'a [u8], usize)> {
+        if self.conditions.is_empty() {
+            self.data.distinct(name)
+        } else {
+            let rows = self.result().await;
+            self.data.facets_by(name, |row| rows.contains(row))
+        }
+    }
+}
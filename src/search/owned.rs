@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Activity, Condition, FieldName};
+
+use super::{Field, Number, Term};
+
+/// A [Condition] that owns its values, so that it can be kept, sent to other threads, serialized and deserialized.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OwnedCondition {
+    Activity(Activity),
+    Term(Term),
+    Row(Number),
+    Uuid(Vec<u128>),
+    LastUpdated(Number),
+    Field(FieldName, Field),
+    Narrow(Vec<OwnedCondition>),
+    Wide(Vec<OwnedCondition>),
+    Not(Box<OwnedCondition>),
+}
+
+impl OwnedCondition {
+    /// Returns the condition to pass to [Search::search](crate::Search::search).
+    pub fn as_condition(&self) -> Condition<'_> {
+        match self {
+            Self::Activity(activity) => Condition::Activity(*activity),
+            Self::Term(term) => Condition::Term(term.clone()),
+            Self::Row(number) => Condition::Row(number),
+            Self::Uuid(uuids) => Condition::Uuid(uuids),
+            Self::LastUpdated(number) => Condition::LastUpdated(number),
+            Self::Field(name, field) => Condition::Field(name.clone(), field),
+            Self::Narrow(_) | Self::Wide(_) | Self::Not(_) => Condition::Owned(self),
+        }
+    }
+
+    /// Calls the function with the condition borrowed as a [Condition], groups included.
+    pub(crate) fn with_condition<R>(&self, f: impl FnOnce(&Condition) -> R) -> R {
+        match self {
+            Self::Narrow(conditions) => f(&Condition::Narrow(
+                &conditions.iter().map(Self::as_condition).collect(),
+            )),
+            Self::Wide(conditions) => f(&Condition::Wide(
+                &conditions.iter().map(Self::as_condition).collect(),
+            )),
+            Self::Not(condition) => f(&Condition::Not(&condition.as_condition())),
+            _ => f(&self.as_condition()),
+        }
+    }
+}
+
+impl From<&Condition<'_>> for OwnedCondition {
+    fn from(condition: &Condition) -> Self {
+        match condition {
+            Condition::Activity(activity) => Self::Activity(*activity),
+            Condition::Term(term) => Self::Term(term.clone()),
+            Condition::Row(number) => Self::Row((*number).clone()),
+            Condition::Uuid(uuids) => Self::Uuid(uuids.to_vec()),
+            Condition::LastUpdated(number) => Self::LastUpdated((*number).clone()),
+            Condition::Field(name, field) => Self::Field(name.clone(), (*field).clone()),
+            Condition::Narrow(conditions) => {
+                Self::Narrow(conditions.iter().map(Self::from).collect())
+            }
+            Condition::Wide(conditions) => Self::Wide(conditions.iter().map(Self::from).collect()),
+            Condition::Not(condition) => Self::Not(Box::new(Self::from(*condition))),
+            Condition::Owned(condition) => (*condition).clone(),
+        }
+    }
+}
+
+impl<'a> Condition<'a> {
+    /// Returns the negated condition if the condition is a negation.
+    pub(crate) fn negated(&self) -> Option<Condition<'a>> {
+        match self {
+            Condition::Not(condition) => Some((*condition).clone()),
+            Condition::Owned(OwnedCondition::Not(condition)) => Some(condition.as_condition()),
+            _ => None,
+        }
+    }
+}
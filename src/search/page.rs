@@ -0,0 +1,220 @@
+use std::num::NonZeroU32;
+
+use idx_binary::{AvltrieeIter, AvltrieeSearch};
+use idx_file::IdxFileAllocator;
+
+use crate::{CustomOrderKey, CustomSort, Order, RowSet, Search};
+
+/// Position after the last row of a page. Pass it to [Search::result_page] to get the next page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cursor {
+    row: NonZeroU32,
+    key: Vec<u8>,
+}
+
+impl Cursor {
+    /// Returns the cursor as bytes, e.g. to hand it to a client.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.row.get().to_le_bytes().to_vec();
+        bytes.extend(&self.key);
+        bytes
+    }
+
+    /// Restores a cursor from [Cursor::to_bytes]. Returns None if the bytes are not a cursor.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let row = NonZeroU32::new(u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?))?;
+        Some(Self {
+            row,
+            key: bytes[4..].to_vec(),
+        })
+    }
+}
+
+impl<'a> Search<'a> {
+    /// Returns the rows after the cursor in the order, at most as many as the limit, and the cursor to get the next page.
+    /// Rows with the same key are ordered by row. Rows inserted or deleted between pages do not shift the pages.
+    /// The returned cursor is None when there are no more rows, or when the limit is 0.
+    /// In a custom order, a cursor whose row is no longer in the result returns an empty page,
+    /// because the position of the row in the order is lost.
+    pub async fn result_page<C: CustomSort>(
+        &self,
+        order: Order<C>,
+        cursor: Option<&Cursor>,
+    ) -> (Vec<NonZeroU32>, Option<Cursor>) {
+        let limit = self.limit.unwrap_or(usize::MAX);
+        if limit == 0 {
+            return (vec![], None);
+        }
+        let rows = self.result().await;
+        let (key, desc) = match &order {
+            Order::Asc(key) => (key, false),
+            Order::Desc(key) => (key, true),
+        };
+        let data = self.data;
+        macro_rules! page_u64 {
+            ($index:expr) => {
+                if let Some(ref index) = $index {
+                    let start = cursor
+                        .and_then(|c| c.key.as_slice().try_into().ok())
+                        .map(u64::from_be_bytes);
+                    page_with_index(&rows, &**index, start.as_ref(), cursor, desc, limit, |v| {
+                        v.to_be_bytes().to_vec()
+                    })
+                } else {
+                    page_by_row(rows.iter().collect(), cursor, desc, limit)
+                }
+            };
+        }
+        match key {
+            CustomOrderKey::Serial => {
+                let start = cursor
+                    .and_then(|c| c.key.as_slice().try_into().ok())
+                    .map(u32::from_be_bytes);
+                page_with_index(
+                    &rows,
+                    &**data.serial,
+                    start.as_ref(),
+                    cursor,
+                    desc,
+                    limit,
+                    |v| v.to_be_bytes().to_vec(),
+                )
+            }
+            CustomOrderKey::Row => page_by_row(rows.iter().collect(), cursor, desc, limit),
+            CustomOrderKey::TermBegin => page_u64!(data.term_begin),
+            CustomOrderKey::TermEnd => page_u64!(data.term_end),
+            CustomOrderKey::LastUpdated => page_u64!(data.last_updated),
+            CustomOrderKey::Field(name) => {
+                if let Some(field) = data.fields.get(name) {
+                    page_with_index(
+                        &rows,
+                        field,
+                        cursor.map(|c| c.key.as_slice()),
+                        cursor,
+                        desc,
+                        limit,
+                        |v| v.to_vec(),
+                    )
+                } else {
+                    page_by_row(rows.iter().collect(), cursor, desc, limit)
+                }
+            }
+            CustomOrderKey::Custom(custom) => {
+                let sorted = if desc { custom.desc() } else { custom.asc() };
+                let mut sorted: Vec<_> = sorted.into_iter().filter(|r| rows.contains(r)).collect();
+                if let Some(cursor) = cursor {
+                    let Some(i) = sorted.iter().position(|r| *r == cursor.row) else {
+                        return (vec![], None);
+                    };
+                    sorted.drain(..=i);
+                }
+                truncate(sorted, limit)
+            }
+        }
+    }
+}
+
+/// Pages rows in row order.
+fn page_by_row(
+    rows: Vec<NonZeroU32>,
+    cursor: Option<&Cursor>,
+    desc: bool,
+    limit: usize,
+) -> (Vec<NonZeroU32>, Option<Cursor>) {
+    let page: Vec<_> = if desc {
+        rows.into_iter()
+            .rev()
+            .filter(|r| cursor.is_none_or(|c| *r < c.row))
+            .collect()
+    } else {
+        rows.into_iter()
+            .filter(|r| cursor.is_none_or(|c| *r > c.row))
+            .collect()
+    };
+    truncate(page, limit)
+}
+
+/// Cuts the rows to the limit, with the cursor after the last row kept if any rows were cut.
+fn truncate(mut page: Vec<NonZeroU32>, limit: usize) -> (Vec<NonZeroU32>, Option<Cursor>) {
+    let more = page.len() > limit;
+    page.truncate(limit);
+    let next = page.last().filter(|_| more).map(|row| Cursor {
+        row: *row,
+        key: vec![],
+    });
+    (page, next)
+}
+
+/// Pages rows in the order of the index, starting from the key of the cursor instead of the beginning of the index.
+fn page_with_index<T: PartialEq, I: ?Sized, S: AvltrieeSearch<T, I, IdxFileAllocator<T>>>(
+    rows: &RowSet,
+    index: &S,
+    start: Option<&I>,
+    cursor: Option<&Cursor>,
+    desc: bool,
+    limit: usize,
+    key: impl Fn(&I) -> Vec<u8>,
+) -> (Vec<NonZeroU32>, Option<Cursor>) {
+    let triee = index.as_ref();
+    let iter = match (start, desc) {
+        (None, false) => triee.iter(),
+        (None, true) => triee.desc_iter(),
+        (Some(start), false) => AvltrieeIter::from_asc(index, start),
+        (Some(start), true) => AvltrieeIter::to_desc(index, start),
+    };
+
+    let mut page = Vec::new();
+    let mut last = None;
+    let mut group: Vec<NonZeroU32> = Vec::new();
+    let mut group_value: Option<&T> = None;
+    // Adds the rows of a group of rows with the same key in row order. Returns false once the page has one row more than the limit.
+    let mut flush = |group: &mut Vec<NonZeroU32>, page: &mut Vec<NonZeroU32>| {
+        if group.is_empty() {
+            return true;
+        }
+        group.sort();
+        if desc {
+            group.reverse();
+        }
+        let value = unsafe { index.value_unchecked(group[0]) };
+        let skip_through = cursor.filter(|_| {
+            start.is_some_and(|start| S::cmp(start, value) == std::cmp::Ordering::Equal)
+        });
+        for row in group.drain(..) {
+            if skip_through.is_some_and(|c| if desc { row >= c.row } else { row <= c.row }) {
+                continue;
+            }
+            if page.len() == limit {
+                return false;
+            }
+            page.push(row);
+            last = Some(value);
+        }
+        true
+    };
+    let mut more = false;
+    for row in iter {
+        if !rows.contains(&row) {
+            continue;
+        }
+        let value: &T = unsafe { triee.node_unchecked(row) };
+        if group_value.is_some_and(|v| v != value) && !flush(&mut group, &mut page) {
+            more = true;
+            break;
+        }
+        group_value = Some(value);
+        group.push(row);
+    }
+    if !more {
+        more = !flush(&mut group, &mut page);
+    }
+    let next = page
+        .last()
+        .zip(last)
+        .filter(|_| more)
+        .map(|(row, value)| Cursor {
+            row: *row,
+            key: key(value),
+        });
+    (page, next)
+}
@@ -0,0 +1,108 @@
+use std::{fmt, str::FromStr, sync::Arc};
+
+use regex::bytes::Regex;
+use regex_syntax::hir::{
+    literal::{ExtractKind, Extractor},
+    Look,
+};
+use serde::{Deserialize, Serialize};
+
+/// A compiled regular expression for [Field::Regex](super::Field::Regex).
+/// Values match if the expression matches any part of them, so anchor it with `^` and `$` to match whole values.
+///
+/// When every match has to start at the beginning of the value, the literal prefixes the matches start with are kept,
+/// so that a search only walks the part of the field index that starts with them.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Pattern(Arc<Compiled>);
+
+struct Compiled {
+    regex: Regex,
+    prefixes: Option<Vec<Vec<u8>>>,
+}
+
+impl Pattern {
+    /// Compiles the expression. Returns an error if it is not a valid expression.
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self(Arc::new(Compiled {
+            regex: Regex::new(pattern)?,
+            prefixes: prefixes(pattern),
+        })))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.regex.as_str()
+    }
+
+    /// Returns true if the expression matches any part of the value.
+    pub fn is_match(&self, value: &[u8]) -> bool {
+        self.0.regex.is_match(value)
+    }
+
+    /// Returns the literals one of which every matching value starts with,
+    /// or None if the expression can match values starting with anything.
+    /// No literal starts with another, so the values starting with each do not overlap.
+    pub fn prefixes(&self) -> Option<&[Vec<u8>]> {
+        self.0.prefixes.as_deref()
+    }
+}
+
+/// Extracts the literal prefixes of an expression anchored at the start.
+/// Prefixes are cut before their first digit, because the field index sorts runs of digits by their numeric value
+/// and values starting with digits are not next to each other in it.
+fn prefixes(pattern: &str) -> Option<Vec<Vec<u8>>> {
+    let hir = regex_syntax::parse(pattern).ok()?;
+    if !hir.properties().look_set_prefix().contains(Look::Start) {
+        return None;
+    }
+    let seq = Extractor::new().kind(ExtractKind::Prefix).extract(&hir);
+    let mut prefixes: Vec<Vec<u8>> = seq
+        .literals()?
+        .iter()
+        .map(|literal| {
+            let bytes = literal.as_bytes();
+            bytes[..bytes
+                .iter()
+                .position(u8::is_ascii_digit)
+                .unwrap_or(bytes.len())]
+                .to_vec()
+        })
+        .collect();
+    prefixes.sort();
+    prefixes.dedup_by(|prefix, shorter| prefix.starts_with(shorter));
+    (!prefixes.iter().any(Vec::is_empty)).then_some(prefixes)
+}
+
+impl FromStr for Pattern {
+    type Err = regex::Error;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        Self::new(pattern)
+    }
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Self::new(&pattern)
+    }
+}
+
+impl From<Pattern> for String {
+    fn from(pattern: Pattern) -> Self {
+        pattern.as_str().to_owned()
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Pattern").field(&self.as_str()).finish()
+    }
+}
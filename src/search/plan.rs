@@ -0,0 +1,95 @@
+use crate::{Condition, Data};
+
+use super::{Field, Number};
+
+/// Most rows counted to estimate a condition. Conditions matching more rows than this are taken to be equally large,
+/// so that planning does not walk the whole index of a condition that matches most rows.
+pub(super) const SAMPLE: usize = 1000;
+
+impl Data {
+    /// Orders the conditions so that the one expected to match the fewest rows comes first.
+    /// Returns each condition with the number of rows it is estimated to match, or None if it has to scan every row.
+    /// Negated conditions and scans come after the conditions that can be looked up in an index.
+    /// Estimates stop at [SAMPLE] rows, and at the smallest estimate so far.
+    pub(super) fn plan<'c, 'a>(
+        &self,
+        conditions: &'c [Condition<'a>],
+    ) -> Vec<(&'c Condition<'a>, Option<usize>)> {
+        let mut cap = SAMPLE;
+        let mut plan: Vec<_> = conditions
+            .iter()
+            .map(|condition| {
+                let estimate = self.estimate(condition, cap);
+                if let Some(estimate) = estimate {
+                    cap = cap.min(estimate);
+                }
+                (condition, estimate)
+            })
+            .collect();
+        plan.sort_by_key(|(condition, estimate)| {
+            (
+                condition.negated().is_some(),
+                estimate.unwrap_or(usize::MAX),
+            )
+        });
+        plan
+    }
+
+    /// Estimates the number of rows matching the condition by counting the rows in its index, stopping at the cap.
+    /// Returns None if the condition has no index and has to check the value of every row.
+    pub(super) fn estimate(&self, condition: &Condition, cap: usize) -> Option<usize> {
+        match condition {
+            Condition::Field(_, field)
+                if !matches!(
+                    field,
+                    Field::Match(_) | Field::Min(_) | Field::Max(_) | Field::Range(_, _)
+                ) =>
+            {
+                self.index_iter(condition)
+                    .map(|iter| iter.take(cap).count())
+            }
+            Condition::Row(condition) => Some(match condition {
+                Number::Range(range) => range.clone().filter(|row| *row > 0).take(cap).count(),
+                Number::In(rows) => rows.len().min(cap),
+                _ => self
+                    .serial
+                    .iter()
+                    .filter(|row| self.matches(*row, &Condition::Row(condition)))
+                    .take(cap)
+                    .count(),
+            }),
+            Condition::Uuid(uuids) => Some(uuids.len().min(cap)),
+            Condition::LastUpdated(Number::In(values)) => {
+                let index = self.last_updated.as_ref()?;
+                Some(
+                    values
+                        .iter()
+                        .flat_map(|v| index.iter_by(&(*v as u64)))
+                        .take(cap)
+                        .count(),
+                )
+            }
+            Condition::Narrow(conditions) => conditions
+                .iter()
+                .filter(|c| c.negated().is_none())
+                .filter_map(|c| self.estimate(c, cap))
+                .min(),
+            Condition::Wide(conditions) => {
+                let mut sum = 0;
+                for c in conditions.iter() {
+                    sum += self.estimate(c, cap)?;
+                    if sum >= cap {
+                        return Some(cap);
+                    }
+                }
+                Some(sum)
+            }
+            Condition::Not(_) => None,
+            Condition::Owned(condition) => condition.with_condition(|c| self.estimate(c, cap)),
+            _ => Some(
+                self.index_iter(condition)
+                    .map_or(0, |iter| iter.take(cap).count()),
+            ),
+        }
+    }
+}
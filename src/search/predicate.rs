@@ -0,0 +1,85 @@
+use std::{cmp::Ordering, num::NonZeroU32};
+
+use idx_binary::{AvltrieeSearch, IdxBinary};
+
+use crate::{Condition, Data};
+
+use super::{Field, Number, Term};
+
+impl Data {
+    /// Returns true if the row matches the condition, looking up only the values of the row.
+    pub(crate) fn matches(&self, row: NonZeroU32, condition: &Condition) -> bool {
+        match condition {
+            Condition::Activity(condition) => self
+                .activity
+                .as_ref()
+                .is_some_and(|index| index.value(row) == Some(&(*condition as u8))),
+            Condition::Term(condition) => self.matches_term(row, condition),
+            Condition::Row(condition) => {
+                self.serial.node(row).is_some() && Self::matches_number(row.get() as u64, condition)
+            }
+            Condition::Uuid(uuids) => self.uuid(row).is_some_and(|uuid| uuids.contains(uuid)),
+            Condition::LastUpdated(condition) => self
+                .last_updated(row)
+                .is_some_and(|v| Self::matches_number(*v, condition)),
+            Condition::Field(name, condition) => self
+                .fields
+                .get(name)
+                .is_some_and(|field| Self::matches_field(row, field, condition)),
+            Condition::Narrow(conditions) => conditions.iter().all(|c| self.matches(row, c)),
+            Condition::Wide(conditions) => conditions.iter().any(|c| self.matches(row, c)),
+            Condition::Not(condition) => {
+                self.serial.node(row).is_some() && !self.matches(row, condition)
+            }
+            Condition::Owned(condition) => condition.with_condition(|c| self.matches(row, c)),
+        }
+    }
+
+    fn matches_term(&self, row: NonZeroU32, condition: &Term) -> bool {
+        match condition {
+            Term::In(base) => {
+                self.term_begin(row).is_some_and(|begin| begin <= base)
+                    && self.term_end(row).is_none_or(|end| *end == 0 || end > base)
+            }
+            Term::Future(base) => self.term_begin(row).is_some_and(|begin| begin >= base),
+            Term::Past(base) => self
+                .term_end(row)
+                .is_some_and(|end| *end >= 1 && end <= base),
+        }
+    }
+
+    fn matches_number(value: u64, condition: &Number) -> bool {
+        let value = value as isize;
+        match condition {
+            Number::Min(min) => value >= *min,
+            Number::Max(max) => value <= *max,
+            Number::Range(range) => range.contains(&value),
+            Number::In(values) => values.contains(&value),
+        }
+    }
+
+    pub(crate) fn matches_field(row: NonZeroU32, field: &crate::Field, condition: &Field) -> bool {
+        let Some(value) = field.value(row) else {
+            return false;
+        };
+        match condition {
+            Field::Match(v) => IdxBinary::cmp(value, v) == Ordering::Equal,
+            Field::Min(min) => IdxBinary::cmp(value, min) != Ordering::Less,
+            Field::Max(max) => IdxBinary::cmp(value, max) != Ordering::Greater,
+            Field::Range(min, max) => {
+                IdxBinary::cmp(value, min) != Ordering::Less
+                    && IdxBinary::cmp(value, max) != Ordering::Greater
+            }
+            Field::Forward(cont) => Self::forward(row, field, cont).1,
+            Field::Partial(cont) => Self::partial(row, field, cont).1,
+            Field::Backward(cont) => Self::backward(row, field, cont).1,
+            Field::ValueForward(cont) => Self::value_forward(row, field, cont).1,
+            Field::ValuePartial(cont) => Self::value_partial(row, field, cont).1,
+            Field::ValueBackward(cont) => Self::value_backward(row, field, cont).1,
+            Field::Words(cont) => Self::words(row, field, cont).1,
+            Field::Phrase(cont) => Self::phrase(row, field, cont).1,
+            Field::WordForward(cont) => Self::word_forward(row, field, cont).1,
+            Field::Regex(pattern) => pattern.is_match(value),
+        }
+    }
+}
@@ -0,0 +1,506 @@
+use std::{num::NonZeroU32, sync::Arc};
+
+use crate::{
+    Activity, CustomOrderKey, Data, Error, FieldName, FieldType, FieldValue, NoCustomSort, Order,
+    Result, Search, Uuid,
+};
+
+use super::{Field, Number, OwnedCondition};
+
+/// A search parsed from text by [Data::parse_query].
+///
+/// A query is a filter followed by optional `ORDER BY`, `LIMIT` and `OFFSET` clauses, for example
+/// `status = "open" AND (price >= 10 OR NOT title ~ "draft") ORDER BY last_updated DESC, title LIMIT 20 OFFSET 40`.
+///
+/// - Comparisons are `key op value`, where op is one of `=`, `!=`, `<`, `<=`, `>`, `>=`,
+///   `~` (contains), `^=` (starts with) and `$=` (ends with).
+/// - `row`, `activity`, `uuid` and `last_updated` compare the columns of the same names; any other key is a field.
+///   Field names that are not plain words, or that clash with those keys, are written between backquotes.
+/// - Values are numbers, words or strings between double quotes. A backslash escapes the next character.
+/// - Keywords are case insensitive.
+#[derive(Clone, Debug)]
+pub struct Query {
+    pub condition: Option<OwnedCondition>,
+    pub orders: Vec<Order<NoCustomSort>>,
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+impl Query {
+    /// Returns a search of the data with the condition, limit and offset of the query.
+    pub fn search<'a>(&'a self, data: &'a Data) -> Search<'a> {
+        let mut search = data.begin_search().offset(self.offset);
+        if let Some(limit) = self.limit {
+            search = search.limit(limit);
+        }
+        if let Some(ref condition) = self.condition {
+            search = search.search(condition.as_condition());
+        }
+        search
+    }
+
+    /// Runs the query and returns the rows in the order of the query, or in row order if it has none.
+    pub async fn result(&self, data: &Data) -> Vec<NonZeroU32> {
+        let orders = if self.orders.is_empty() {
+            vec![Order::Asc(CustomOrderKey::Row)]
+        } else {
+            self.orders.clone()
+        };
+        self.search(data).result_with_sort(orders).await
+    }
+}
+
+impl Data {
+    /// Parses a query. See [Query] for the syntax.
+    /// Values compared with typed fields are encoded as the type of the field.
+    pub fn parse_query(&self, text: &str) -> Result<Query> {
+        Parser {
+            data: self,
+            tokens: tokenize(text)?,
+            index: 0,
+            end: text.chars().count() + 1,
+        }
+        .query()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Name(String),
+    Text(String),
+    Number(String),
+    Op(&'static str),
+    Open,
+    Close,
+    Comma,
+}
+
+const OPERATORS: [&str; 9] = ["=", "!=", "<", "<=", ">", ">=", "~", "^=", "$="];
+
+fn error<T>(position: usize, reason: impl Into<String>) -> Result<T> {
+    Err(Error::Query {
+        position,
+        reason: reason.into(),
+    })
+}
+
+/// Splits the text into tokens with the column each starts at.
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().zip(1..).peekable();
+    while let Some(&(c, column)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Comma,
+                }
+            }
+            '"' | '`' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some(('\\', _)) => match chars.next() {
+                            Some((c, _)) => value.push(c),
+                            None => return error(column, "unterminated string"),
+                        },
+                        Some((q, _)) if q == c => break,
+                        Some((c, _)) => value.push(c),
+                        None => return error(column, "unterminated string"),
+                    }
+                }
+                if c == '"' {
+                    Token::Text(value)
+                } else {
+                    Token::Name(value)
+                }
+            }
+            '=' | '!' | '<' | '>' | '~' | '^' | '$' => {
+                let mut op = String::from(c);
+                chars.next();
+                if let Some(&('=', _)) = chars.peek() {
+                    op.push('=');
+                    chars.next();
+                }
+                match OPERATORS.iter().find(|o| **o == op) {
+                    Some(op) => Token::Op(op),
+                    None => return error(column, format!("unknown operator {}", op)),
+                }
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let mut number = String::new();
+                while let Some(&(c, _)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '-' || c == '+' || c == '.') {
+                        break;
+                    }
+                    number.push(c);
+                    chars.next();
+                }
+                Token::Number(number)
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut word = String::new();
+                while let Some(&(c, _)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                Token::Word(word)
+            }
+            c => return error(column, format!("unexpected character {}", c)),
+        };
+        tokens.push((column, token));
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    data: &'a Data,
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    /// Column just past the end of the text, reported when the text ends too early.
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.index)
+            .map_or(self.end, |(column, _)| *column)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Skips the keyword if it comes next.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.at_keyword(keyword);
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    /// Skips the token if it comes next.
+    fn token(&mut self, token: Token) -> bool {
+        let found = self.peek() == Some(&token);
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn query(mut self) -> Result<Query> {
+        let condition = if self.peek().is_none()
+            || ["ORDER", "LIMIT", "OFFSET"]
+                .iter()
+                .any(|keyword| self.at_keyword(keyword))
+        {
+            None
+        } else {
+            Some(self.or()?)
+        };
+        let mut orders = Vec::new();
+        if self.keyword("ORDER") {
+            if !self.keyword("BY") {
+                return error(self.column(), "expected BY after ORDER");
+            }
+            loop {
+                orders.push(self.order()?);
+                if !self.token(Token::Comma) {
+                    break;
+                }
+            }
+        }
+        let limit = if self.keyword("LIMIT") {
+            Some(self.count("LIMIT")?)
+        } else {
+            None
+        };
+        let offset = if self.keyword("OFFSET") {
+            self.count("OFFSET")?
+        } else {
+            0
+        };
+        if self.peek().is_some() {
+            return error(self.column(), "expected AND, OR, ORDER BY, LIMIT or OFFSET");
+        }
+        Ok(Query {
+            condition,
+            orders,
+            limit,
+            offset,
+        })
+    }
+
+    fn or(&mut self) -> Result<OwnedCondition> {
+        let mut conditions = vec![self.and()?];
+        while self.keyword("OR") {
+            conditions.push(self.and()?);
+        }
+        Ok(if conditions.len() == 1 {
+            conditions.pop().unwrap()
+        } else {
+            OwnedCondition::Wide(conditions)
+        })
+    }
+
+    fn and(&mut self) -> Result<OwnedCondition> {
+        let mut conditions = vec![self.unary()?];
+        while self.keyword("AND") {
+            conditions.push(self.unary()?);
+        }
+        Ok(if conditions.len() == 1 {
+            conditions.pop().unwrap()
+        } else {
+            OwnedCondition::Narrow(conditions)
+        })
+    }
+
+    fn unary(&mut self) -> Result<OwnedCondition> {
+        if self.keyword("NOT") {
+            Ok(OwnedCondition::Not(Box::new(self.unary()?)))
+        } else if self.token(Token::Open) {
+            let condition = self.or()?;
+            if !self.token(Token::Close) {
+                return error(self.column(), "expected )");
+            }
+            Ok(condition)
+        } else {
+            self.comparison()
+        }
+    }
+
+    fn comparison(&mut self) -> Result<OwnedCondition> {
+        let key_column = self.column();
+        let key = match self.next() {
+            Some((_, Token::Word(word))) => Key::from_word(word),
+            Some((_, Token::Name(name))) => Key::Field(FieldName::new(name)),
+            _ => {
+                return error(
+                    key_column,
+                    "expected a field name or one of row, activity, uuid and last_updated",
+                )
+            }
+        };
+        let disabled = match key {
+            Key::Uuid => self.data.uuid.is_none(),
+            Key::LastUpdated => self.data.last_updated.is_none(),
+            _ => false,
+        };
+        if disabled {
+            return error(
+                key_column,
+                format!("{} is disabled in the data options", key),
+            );
+        }
+        let op_column = self.column();
+        let Some((_, Token::Op(op))) = self.next() else {
+            return error(op_column, format!("expected an operator after {}", key));
+        };
+        let column = self.column();
+        let value = match self.next() {
+            Some((_, Token::Word(v) | Token::Text(v) | Token::Number(v))) => v,
+            _ => return error(column, format!("expected a value after {}", op)),
+        };
+        let unsupported = || error(op_column, format!("{} cannot be compared with {}", key, op));
+        match key {
+            Key::Row | Key::LastUpdated => {
+                let Ok(n) = value.parse::<isize>() else {
+                    return error(column, format!("{} is not a number", value));
+                };
+                let number = match op {
+                    "=" | "!=" => Number::In(vec![n]),
+                    ">=" => Number::Min(n),
+                    ">" => Number::Min(n.saturating_add(1)),
+                    "<=" => Number::Max(n),
+                    "<" => Number::Max(n.saturating_sub(1)),
+                    _ => return unsupported(),
+                };
+                let condition = if let Key::Row = key {
+                    OwnedCondition::Row(number)
+                } else {
+                    OwnedCondition::LastUpdated(number)
+                };
+                Ok(negate_if(op == "!=", condition))
+            }
+            Key::Activity => {
+                let activity = match value.to_ascii_lowercase().as_str() {
+                    "active" | "1" => Activity::Active,
+                    "inactive" | "0" => Activity::Inactive,
+                    _ => return error(column, "expected active or inactive"),
+                };
+                match op {
+                    "=" => Ok(OwnedCondition::Activity(activity)),
+                    "!=" => Ok(OwnedCondition::Activity(match activity {
+                        Activity::Active => Activity::Inactive,
+                        Activity::Inactive => Activity::Active,
+                    })),
+                    _ => unsupported(),
+                }
+            }
+            Key::Uuid => {
+                let Ok(uuid) = Uuid::parse_str(&value) else {
+                    return error(column, format!("{} is not a UUID", value));
+                };
+                let condition = OwnedCondition::Uuid(vec![uuid.as_u128()]);
+                match op {
+                    "=" => Ok(condition),
+                    "!=" => Ok(negate_if(true, condition)),
+                    _ => unsupported(),
+                }
+            }
+            Key::Field(name) => {
+                let field_type = self.data.field_type(&name);
+                let text = || Arc::new(value.clone());
+                let field = match op {
+                    "~" => Field::Partial(text()),
+                    "^=" => Field::Forward(text()),
+                    "$=" => Field::Backward(text()),
+                    _ => {
+                        let Some(bytes) = encode(field_type, &value) else {
+                            return error(
+                                column,
+                                format!(
+                                    "{} is not a value of {} field {}",
+                                    value, field_type, name
+                                ),
+                            );
+                        };
+                        let condition = |field| OwnedCondition::Field(name.clone(), field);
+                        return Ok(match op {
+                            "=" => condition(Field::Match(bytes)),
+                            "!=" => negate_if(true, condition(Field::Match(bytes))),
+                            ">=" => condition(Field::Min(bytes)),
+                            "<=" => condition(Field::Max(bytes)),
+                            ">" | "<" => OwnedCondition::Narrow(vec![
+                                condition(if op == ">" {
+                                    Field::Min(bytes.clone())
+                                } else {
+                                    Field::Max(bytes.clone())
+                                }),
+                                negate_if(true, condition(Field::Match(bytes))),
+                            ]),
+                            _ => unreachable!(),
+                        });
+                    }
+                };
+                if matches!(field_type, FieldType::Bytes | FieldType::String) {
+                    Ok(OwnedCondition::Field(name, field))
+                } else {
+                    error(
+                        op_column,
+                        format!("{} needs a text field but {} is {}", op, name, field_type),
+                    )
+                }
+            }
+        }
+    }
+
+    fn order(&mut self) -> Result<Order<NoCustomSort>> {
+        let column = self.column();
+        let key = match self.next() {
+            Some((_, Token::Word(word))) => match word.to_ascii_lowercase().as_str() {
+                "row" => CustomOrderKey::Row,
+                "serial" => CustomOrderKey::Serial,
+                "term_begin" => CustomOrderKey::TermBegin,
+                "term_end" => CustomOrderKey::TermEnd,
+                "last_updated" => CustomOrderKey::LastUpdated,
+                _ => CustomOrderKey::Field(FieldName::new(word)),
+            },
+            Some((_, Token::Name(name))) => CustomOrderKey::Field(FieldName::new(name)),
+            _ => return error(column, "expected a field name to order by"),
+        };
+        Ok(if self.keyword("DESC") {
+            Order::Desc(key)
+        } else {
+            self.keyword("ASC");
+            Order::Asc(key)
+        })
+    }
+
+    fn count(&mut self, clause: &str) -> Result<usize> {
+        let column = self.column();
+        match self.next() {
+            Some((_, Token::Number(n))) if n.parse::<usize>().is_ok() => Ok(n.parse().unwrap()),
+            _ => error(
+                column,
+                format!("expected a number of rows after {}", clause),
+            ),
+        }
+    }
+}
+
+enum Key {
+    Row,
+    Activity,
+    Uuid,
+    LastUpdated,
+    Field(FieldName),
+}
+
+impl Key {
+    fn from_word(word: String) -> Self {
+        match word.to_ascii_lowercase().as_str() {
+            "row" => Self::Row,
+            "activity" => Self::Activity,
+            "uuid" => Self::Uuid,
+            "last_updated" => Self::LastUpdated,
+            _ => Self::Field(FieldName::new(word)),
+        }
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Row => f.write_str("row"),
+            Self::Activity => f.write_str("activity"),
+            Self::Uuid => f.write_str("uuid"),
+            Self::LastUpdated => f.write_str("last_updated"),
+            Self::Field(name) => f.write_str(name),
+        }
+    }
+}
+
+fn negate_if(negate: bool, condition: OwnedCondition) -> OwnedCondition {
+    if negate {
+        OwnedCondition::Not(Box::new(condition))
+    } else {
+        condition
+    }
+}
+
+/// Returns the bytes stored for the text as a value of the field type.
+fn encode(field_type: FieldType, text: &str) -> Option<Vec<u8>> {
+    let value = match field_type {
+        FieldType::Bytes | FieldType::String => return Some(text.as_bytes().to_vec()),
+        FieldType::I64 => FieldValue::I64(text.parse().ok()?),
+        FieldType::U64 => FieldValue::U64(text.parse().ok()?),
+        FieldType::F64 => FieldValue::F64(text.parse().ok()?),
+        FieldType::Bool => FieldValue::Bool(text.parse().ok()?),
+        FieldType::Timestamp => FieldValue::Timestamp(text.parse().ok()?),
+    };
+    Some(value.to_bytes())
+}
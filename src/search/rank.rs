@@ -0,0 +1,40 @@
+use std::num::NonZeroU32;
+
+use idx_binary::AvltrieeSearch;
+
+use crate::{
+    full_text::{self, FullTextIndex},
+    FieldName, Search,
+};
+
+impl<'a> Search<'a> {
+    /// Returns the matching rows with a score of how well the words of the field match the words of the text, highest first.
+    /// Scores come from the full-text index of the field, or from the values of the matching rows if the field has none.
+    /// Rows with the same score are in row order. Skips the offset and returns at most the limit.
+    pub async fn rank(&self, name: &FieldName, text: &str) -> Vec<(NonZeroU32, f64)> {
+        let rows = self.result().await;
+        let words: Vec<_> = full_text::words(text).collect();
+        let built;
+        let index = match self.data.full_text.get(name) {
+            Some(index) => index,
+            None => {
+                let field = self.data.fields.get(name);
+                built = FullTextIndex::build(
+                    rows.iter()
+                        .filter_map(|row| Some((row, field?.value(row)?))),
+                );
+                &built
+            }
+        };
+        let mut ranked: Vec<_> = rows
+            .iter()
+            .map(|row| (row, index.score(row, &words)))
+            .collect();
+        ranked.sort_by(|(a_row, a), (b_row, b)| b.total_cmp(a).then(a_row.cmp(b_row)));
+        ranked
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
@@ -1,16 +1,16 @@
-use std::num::NonZeroU32;
+use std::{num::NonZeroU32, time::Instant};
 
 use async_recursion::async_recursion;
 use futures::future;
 use idx_binary::{AvltrieeIter, AvltrieeSearch};
 
-use crate::{Condition, CustomSort, Data, FieldName, Order, RowSet, Search};
+use crate::{full_text, Condition, CustomSort, Data, FieldName, Order, RowSet, Search};
 
-use super::{Field, Number, Term};
+use super::{plan::SAMPLE, Evaluation, Field, Number, OwnedCondition, Pattern, Step, Term};
 
 impl<'a> Search<'a> {
     pub async fn result(&self) -> RowSet {
-        if self.conditions.len() > 0 {
+        if !self.conditions.is_empty() {
             self.data.result(&self.conditions).await
         } else {
             self.data.all()
@@ -18,7 +18,12 @@ impl<'a> Search<'a> {
     }
 
     pub async fn result_with_sort<C: CustomSort>(&self, orders: Vec<Order<C>>) -> Vec<NonZeroU32> {
-        self.data.sort(&self.result().await, &orders)
+        let limit = self.limit.map(|limit| self.offset + limit);
+        let mut rows = self
+            .data
+            .sort_with_limit(&self.result().await, &orders, limit);
+        rows.drain(..self.offset.min(rows.len()));
+        rows
     }
 }
 
@@ -41,26 +46,100 @@ impl Data {
             Condition::LastUpdated(condition) => self.result_last_updated(condition),
             Condition::Uuid(uuid) => self.result_uuid(uuid),
             Condition::Narrow(conditions) => self.result(conditions).await,
-            Condition::Wide(conditions) => {
-                future::join_all(conditions.into_iter().map(|c| self.result_condition(c)))
-                    .await
-                    .into_iter()
-                    .flatten()
-                    .collect()
-            }
+            Condition::Wide(conditions) => self.result_wide(conditions).await,
+            Condition::Not(condition) => self.all() - self.result_condition(condition).await,
+            Condition::Owned(condition) => match condition {
+                OwnedCondition::Narrow(conditions) => {
+                    let conditions: Vec<_> = conditions.iter().map(|c| c.as_condition()).collect();
+                    self.result(&conditions).await
+                }
+                OwnedCondition::Wide(conditions) => {
+                    let conditions: Vec<_> = conditions.iter().map(|c| c.as_condition()).collect();
+                    self.result_wide(&conditions).await
+                }
+                OwnedCondition::Not(condition) => {
+                    self.all() - self.result_condition(&condition.as_condition()).await
+                }
+                _ => self.result_condition(&condition.as_condition()).await,
+            },
         }
     }
 
-    #[async_recursion(?Send)]
-    async fn result(&self, conditions: &Vec<Condition>) -> RowSet {
-        let (mut rows, _index, fs) =
-            future::select_all(conditions.into_iter().map(|c| self.result_condition(c))).await;
-        for r in future::join_all(fs).await.into_iter() {
-            rows.retain(|v| r.contains(v));
+    /// Rows that match any of the conditions.
+    async fn result_wide(&self, conditions: &[Condition<'_>]) -> RowSet {
+        let mut rows = RowSet::new();
+        for r in future::join_all(conditions.iter().map(|c| self.result_condition(c))).await {
+            rows |= r;
         }
         rows
     }
 
+    /// Rows that match all conditions.
+    async fn result(&self, conditions: &[Condition<'_>]) -> RowSet {
+        self.execute(conditions, None).await
+    }
+
+    /// Rows that match all conditions, recording each step in the steps if given.
+    /// The condition expected to match the fewest rows is looked up first, and the others are checked against the rows it found
+    /// instead of being looked up, unless they are expected to match even fewer rows.
+    #[async_recursion(?Send)]
+    pub(super) async fn execute(
+        &self,
+        conditions: &[Condition],
+        mut steps: Option<&'async_recursion mut Vec<Step>>,
+    ) -> RowSet {
+        let mut rows: Option<RowSet> = None;
+        for (condition, estimate) in self.plan(conditions) {
+            let start = Instant::now();
+            let evaluation = match &rows {
+                None if estimate.is_some() => Evaluation::Lookup,
+                None => Evaluation::Filter,
+                Some(found) if found.is_empty() => break,
+                Some(found) => {
+                    let fewer = |estimate: Option<usize>| estimate.is_some_and(|e| e < found.len());
+                    // An estimate that reached the sample size only says that the condition matches at least that many rows.
+                    let estimate = match estimate {
+                        Some(e) if e >= SAMPLE => self.estimate(condition, found.len()),
+                        _ => estimate,
+                    };
+                    match condition.negated() {
+                        Some(c) if fewer(self.estimate(&c, found.len())) => Evaluation::Subtract,
+                        _ if fewer(estimate) => Evaluation::Lookup,
+                        _ => Evaluation::Filter,
+                    }
+                }
+            };
+            match evaluation {
+                Evaluation::Lookup => {
+                    let matched = self.result_condition(condition).await;
+                    match rows {
+                        Some(ref mut found) => *found &= matched,
+                        None => rows = Some(matched),
+                    }
+                }
+                Evaluation::Subtract => {
+                    if let (Some(found), Some(c)) = (rows.as_mut(), condition.negated()) {
+                        *found -= self.result_condition(&c).await;
+                    }
+                }
+                Evaluation::Filter => rows
+                    .get_or_insert_with(|| self.all())
+                    .retain(|row| self.matches(*row, condition)),
+            }
+            if let Some(steps) = steps.as_deref_mut() {
+                steps.push(Step {
+                    condition: format!("{:?}", condition),
+                    access: self.access(condition),
+                    evaluation,
+                    estimate,
+                    rows: rows.as_ref().map_or(0, |rows| rows.len()),
+                    time: start.elapsed(),
+                });
+            }
+        }
+        rows.unwrap_or_else(|| self.all())
+    }
+
     fn result_last_updated(&self, condition: &Number) -> RowSet {
         if let Some(ref f) = self.last_updated {
             match condition {
@@ -75,11 +154,7 @@ impl Data {
                 Number::Range(range) => f
                     .iter_range(&(*range.start() as u64), &(*range.end() as u64))
                     .collect(),
-                Number::In(rows) => rows
-                    .into_iter()
-                    .map(|i| f.iter_by(&(*i as u64)))
-                    .flatten()
-                    .collect(),
+                Number::In(rows) => rows.iter().flat_map(|i| f.iter_by(&(*i as u64))).collect(),
             }
         } else {
             unreachable!();
@@ -88,11 +163,7 @@ impl Data {
 
     fn result_uuid(&self, uuids: &[u128]) -> RowSet {
         if let Some(ref index) = self.uuid {
-            uuids
-                .into_iter()
-                .map(|uuid| index.iter_by(&uuid))
-                .flatten()
-                .collect()
+            uuids.iter().flat_map(|uuid| index.iter_by(uuid)).collect()
         } else {
             unreachable!();
         }
@@ -102,13 +173,13 @@ impl Data {
             Term::In(base) => {
                 if let Some(ref term_begin) = self.term_begin {
                     if let Some(ref term_end) = self.term_end {
-                        return term_begin
+                        term_begin
                             .iter_to(base)
-                            .filter_map(|row| {
-                                let end = term_end.value(row).unwrap_or(&0);
-                                (*end == 0 || end > base).then_some(row)
+                            .filter(|row| {
+                                let end = term_end.value(*row).unwrap_or(&0);
+                                *end == 0 || end > base
                             })
-                            .collect();
+                            .collect()
                     } else {
                         unreachable!();
                     }
@@ -118,14 +189,14 @@ impl Data {
             }
             Term::Future(base) => {
                 if let Some(ref index) = self.term_begin {
-                    return index.iter_from(&base).collect();
+                    index.iter_from(base).collect()
                 } else {
                     unreachable!();
                 }
             }
             Term::Past(base) => {
                 if let Some(ref index) = self.term_end {
-                    return index.iter_range(&1, &base).collect();
+                    index.iter_range(&1, base).collect()
                 } else {
                     unreachable!();
                 }
@@ -139,14 +210,14 @@ impl Data {
                 let row = *row;
                 self.serial
                     .iter()
-                    .filter_map(|i| (i.get() as isize >= row).then_some(i))
+                    .filter(|i| i.get() as isize >= row)
                     .collect()
             }
             Number::Max(row) => {
                 let row = *row;
                 self.serial
                     .iter()
-                    .filter_map(|i| (i.get() as isize <= row).then_some(i))
+                    .filter(|i| i.get() as isize <= row)
                     .collect()
             }
             Number::Range(range) => range
@@ -161,7 +232,7 @@ impl Data {
                 })
                 .collect(),
             Number::In(rows) => rows
-                .into_iter()
+                .iter()
                 .filter_map(|i| {
                     let i = *i;
                     (i > 0
@@ -176,12 +247,17 @@ impl Data {
     }
 
     pub fn result_field(&self, name: &FieldName, condition: &Field) -> RowSet {
-        if let Some(field) = self.fields.get(name) {
+        if let Some(rows) = self
+            .full_text_search(name, condition)
+            .or_else(|| self.ngram_search(name, condition))
+        {
+            rows
+        } else if let Some(field) = self.fields.get(name) {
             match condition {
                 Field::Match(v) => AvltrieeIter::by(field, v).collect(),
                 Field::Min(min) => AvltrieeIter::from_asc(field, min).collect(),
-                Field::Max(max) => AvltrieeIter::to_asc(field, &max).collect(),
-                Field::Range(min, max) => AvltrieeIter::range_asc(field, &min, &max).collect(),
+                Field::Max(max) => AvltrieeIter::to_asc(field, max).collect(),
+                Field::Range(min, max) => AvltrieeIter::range_asc(field, min, max).collect(),
                 Field::Forward(cont) => Self::result_field_sub(field, cont, Self::forward),
                 Field::Partial(cont) => Self::result_field_sub(field, cont, Self::partial),
                 Field::Backward(cont) => Self::result_field_sub(field, cont, Self::backward),
@@ -194,6 +270,19 @@ impl Data {
                 Field::ValueBackward(cont) => {
                     Self::result_field_sub(field, cont, Self::value_backward)
                 }
+                Field::Words(cont) => Self::result_field_sub(field, cont, Self::words),
+                Field::Phrase(cont) => Self::result_field_sub(field, cont, Self::phrase),
+                Field::WordForward(cont) => Self::result_field_sub(field, cont, Self::word_forward),
+                Field::Regex(pattern) => match Self::prefix_iter(field, pattern) {
+                    Some(iter) => iter
+                        .filter(|row| pattern.is_match(unsafe { field.value_unchecked(*row) }))
+                        .collect(),
+                    None => field
+                        .as_ref()
+                        .iter()
+                        .filter(|row| pattern.is_match(unsafe { field.value_unchecked(*row) }))
+                        .collect(),
+                },
             }
         } else {
             RowSet::default()
@@ -213,19 +302,19 @@ impl Data {
             .collect()
     }
 
-    fn forward(row: NonZeroU32, field: &crate::Field, cont: &str) -> (NonZeroU32, bool) {
+    pub(super) fn forward(row: NonZeroU32, field: &crate::Field, cont: &str) -> (NonZeroU32, bool) {
         (
             row,
             field
                 .value(row)
-                .map_or(false, |bytes| bytes.starts_with(cont.as_bytes())),
+                .is_some_and(|bytes| bytes.starts_with(cont.as_bytes())),
         )
     }
 
-    fn partial(row: NonZeroU32, field: &crate::Field, cont: &str) -> (NonZeroU32, bool) {
+    pub(super) fn partial(row: NonZeroU32, field: &crate::Field, cont: &str) -> (NonZeroU32, bool) {
         (
             row,
-            field.value(row).map_or(false, |bytes| {
+            field.value(row).is_some_and(|bytes| {
                 let len = cont.len();
                 len <= bytes.len() && {
                     let cont_bytes = cont.as_bytes();
@@ -238,28 +327,40 @@ impl Data {
         )
     }
 
-    fn backward(row: NonZeroU32, field: &crate::Field, cont: &str) -> (NonZeroU32, bool) {
+    pub(super) fn backward(
+        row: NonZeroU32,
+        field: &crate::Field,
+        cont: &str,
+    ) -> (NonZeroU32, bool) {
         (
             row,
             field
                 .value(row)
-                .map_or(false, |bytes| bytes.ends_with(cont.as_bytes())),
+                .is_some_and(|bytes| bytes.ends_with(cont.as_bytes())),
         )
     }
 
-    fn value_forward(row: NonZeroU32, field: &crate::Field, cont: &str) -> (NonZeroU32, bool) {
+    pub(super) fn value_forward(
+        row: NonZeroU32,
+        field: &crate::Field,
+        cont: &str,
+    ) -> (NonZeroU32, bool) {
         (
             row,
             field
                 .value(row)
-                .map_or(false, |bytes| cont.as_bytes().starts_with(bytes)),
+                .is_some_and(|bytes| cont.as_bytes().starts_with(bytes)),
         )
     }
 
-    fn value_partial(row: NonZeroU32, field: &crate::Field, cont: &str) -> (NonZeroU32, bool) {
+    pub(super) fn value_partial(
+        row: NonZeroU32,
+        field: &crate::Field,
+        cont: &str,
+    ) -> (NonZeroU32, bool) {
         (
             row,
-            field.value(row).map_or(false, |bytes| {
+            field.value(row).is_some_and(|bytes| {
                 cont.as_bytes()
                     .windows(bytes.len())
                     .position(|window| window == bytes)
@@ -268,12 +369,72 @@ impl Data {
         )
     }
 
-    fn value_backward(row: NonZeroU32, field: &crate::Field, cont: &str) -> (NonZeroU32, bool) {
+    pub(super) fn value_backward(
+        row: NonZeroU32,
+        field: &crate::Field,
+        cont: &str,
+    ) -> (NonZeroU32, bool) {
         (
             row,
             field
                 .value(row)
-                .map_or(false, |bytes| cont.as_bytes().ends_with(bytes)),
+                .is_some_and(|bytes| cont.as_bytes().ends_with(bytes)),
+        )
+    }
+
+    pub(super) fn words(row: NonZeroU32, field: &crate::Field, cont: &str) -> (NonZeroU32, bool) {
+        (
+            row,
+            field.value(row).is_some_and(|bytes| {
+                let values: Vec<_> = full_text::words(&String::from_utf8_lossy(bytes)).collect();
+                let mut words = full_text::words(cont).peekable();
+                words.peek().is_some() && words.all(|word| values.contains(&word))
+            }),
+        )
+    }
+
+    pub(super) fn phrase(row: NonZeroU32, field: &crate::Field, cont: &str) -> (NonZeroU32, bool) {
+        (
+            row,
+            field.value(row).is_some_and(|bytes| {
+                let values: Vec<_> = full_text::words(&String::from_utf8_lossy(bytes)).collect();
+                let words: Vec<_> = full_text::words(cont).collect();
+                !words.is_empty() && values.windows(words.len()).any(|window| window == words)
+            }),
+        )
+    }
+
+    pub(super) fn word_forward(
+        row: NonZeroU32,
+        field: &crate::Field,
+        cont: &str,
+    ) -> (NonZeroU32, bool) {
+        let cont = cont.to_lowercase();
+        (
+            row,
+            field.value(row).is_some_and(|bytes| {
+                full_text::words(&String::from_utf8_lossy(bytes))
+                    .any(|word| word.starts_with(&cont))
+            }),
+        )
+    }
+
+    /// Walks the rows of the field whose values start with a literal prefix of the pattern,
+    /// or returns None if the pattern has none.
+    pub(super) fn prefix_iter<'a>(
+        field: &'a crate::Field,
+        pattern: &Pattern,
+    ) -> Option<impl Iterator<Item = NonZeroU32> + 'a> {
+        Some(
+            pattern
+                .prefixes()?
+                .to_vec()
+                .into_iter()
+                .flat_map(move |prefix| {
+                    AvltrieeIter::from_asc(field, prefix.as_slice()).take_while(move |row| {
+                        unsafe { field.value_unchecked(*row) }.starts_with(&prefix)
+                    })
+                }),
         )
     }
 }
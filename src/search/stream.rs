@@ -0,0 +1,93 @@
+use std::num::NonZeroU32;
+
+use idx_binary::AvltrieeIter;
+
+use crate::{Condition, Data, Search};
+
+use super::{Field, Number, Term};
+
+type RowIter<'a> = Box<dyn Iterator<Item = NonZeroU32> + 'a>;
+
+/// Rows matching a search, found one at a time as the caller asks for them.
+pub struct RowStream<'a> {
+    iter: RowIter<'a>,
+}
+
+impl Iterator for RowStream<'_> {
+    type Item = NonZeroU32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+}
+
+impl<'a> Search<'a> {
+    /// Returns the matching rows without collecting them into a [RowSet](crate::RowSet).
+    /// Walks the index of the first condition that can be walked and checks the values of each row against all conditions,
+    /// so rows come in the order of that index, or in serial order if no condition has one.
+    pub fn stream(&self) -> RowStream<'_> {
+        let data = self.data;
+        let conditions = &self.conditions;
+        let iter = conditions
+            .iter()
+            .find_map(|c| data.index_iter(c))
+            .unwrap_or_else(|| Box::new(data.serial.iter()));
+        RowStream {
+            iter: Box::new(
+                iter.filter(move |row| conditions.iter().all(|c| data.matches(*row, c))),
+            ),
+        }
+    }
+}
+
+impl Data {
+    /// Returns an iterator over the index of the condition that yields at least the matching rows, if the condition has an index to walk.
+    pub(super) fn index_iter<'a>(&'a self, condition: &Condition) -> Option<RowIter<'a>> {
+        Some(match condition {
+            Condition::Activity(activity) => {
+                Box::new(self.activity.as_ref()?.iter_by(&(*activity as u8)))
+            }
+            Condition::Term(Term::In(base)) => Box::new(self.term_begin.as_ref()?.iter_to(base)),
+            Condition::Term(Term::Future(base)) => {
+                Box::new(self.term_begin.as_ref()?.iter_from(base))
+            }
+            Condition::Term(Term::Past(base)) => {
+                Box::new(self.term_end.as_ref()?.iter_range(&1, base))
+            }
+            Condition::Uuid([uuid]) => Box::new(self.uuid.as_ref()?.iter_by(uuid)),
+            Condition::LastUpdated(condition) => {
+                let index = self.last_updated.as_ref()?;
+                match condition {
+                    Number::Min(min) => Box::new(index.iter_from(&(*min as u64))),
+                    Number::Max(max) => Box::new(index.iter_to(&(*max as u64))),
+                    Number::Range(range) => {
+                        Box::new(index.iter_range(&(*range.start() as u64), &(*range.end() as u64)))
+                    }
+                    Number::In(_) => return None,
+                }
+            }
+            Condition::Field(name, condition) => {
+                let field = self.fields.get(name)?;
+                match condition {
+                    Field::Match(v) => Box::new(AvltrieeIter::by(field, v)),
+                    Field::Min(min) => Box::new(AvltrieeIter::from_asc(field, min)),
+                    Field::Max(max) => Box::new(AvltrieeIter::to_asc(field, max)),
+                    Field::Range(min, max) => Box::new(AvltrieeIter::range_asc(field, min, max)),
+                    Field::Regex(pattern) => Box::new(Self::prefix_iter(field, pattern)?),
+                    _ => Box::new(
+                        self.full_text_search(name, condition)
+                            .or_else(|| self.ngram_search(name, condition))?
+                            .into_iter(),
+                    ),
+                }
+            }
+            Condition::Narrow(conditions) => {
+                return conditions.iter().find_map(|c| self.index_iter(c));
+            }
+            Condition::Owned(condition) => {
+                return condition.with_condition(|c| self.index_iter(c));
+            }
+            _ => return None,
+        })
+    }
+}
@@ -1,11 +1,13 @@
-use idx_binary::{AvltrieeUpdate, IdxFile};
+use hashbrown::HashSet;
+use idx_binary::{AvltrieeSearch, AvltrieeUpdate, IdxFile};
 use std::{num::NonZeroU32, path::PathBuf};
 
-use crate::RowFragment;
+use crate::{check_file, open_idx_file, Result, RowFragment};
 
 pub(crate) struct SerialNumber {
     serial: IdxFile<u32>,
     fragment: RowFragment,
+    max_row: u32,
 }
 
 impl std::ops::Deref for SerialNumber {
@@ -16,37 +18,106 @@ impl std::ops::Deref for SerialNumber {
 }
 
 impl SerialNumber {
-    pub fn new(path: PathBuf, reserve_unit: u32) -> Self {
+    pub fn new(path: PathBuf, reserve_unit: u32) -> Result<Self> {
         let file_name = path.file_name().map_or("".into(), |f| f.to_string_lossy());
-        SerialNumber {
-            serial: IdxFile::new(
+        let mut serial = SerialNumber {
+            serial: open_idx_file(
                 {
                     let mut path = path.clone();
                     path.set_file_name(&(file_name.to_string() + ".i"));
                     path
                 },
                 reserve_unit,
-            ),
-            fragment: RowFragment::new({
+            )?,
+            fragment: {
                 let mut path = path.clone();
                 path.set_file_name(&(file_name.into_owned() + ".f"));
-                path
-            }),
-        }
+                check_file(&path, std::mem::size_of::<u32>() as u64)?;
+                RowFragment::new(path)?
+            },
+            max_row: 0,
+        };
+        serial.max_row = serial.max_row();
+        Ok(serial)
+    }
+
+    /// Returns the largest row that is in use or blank.
+    /// The rows count of the index cannot be used because updating a row sets it to that row.
+    fn max_row(&self) -> u32 {
+        self.serial
+            .iter()
+            .chain(self.fragment.blanks())
+            .map(|row| row.get())
+            .max()
+            .unwrap_or(0)
     }
 
-    pub fn delete(&mut self, row: NonZeroU32) {
+    /// Deletes the row and adds it to the blank list. Returns false without doing anything if the row does not exist.
+    pub fn delete(&mut self, row: NonZeroU32) -> Result<bool> {
+        if row.get() > self.max_row || self.serial.value(row).is_none() {
+            return Ok(false);
+        }
+        self.fragment.insert_blank(row)?;
         self.serial.delete(row);
-        self.fragment.insert_blank(row);
+        Ok(true)
+    }
+
+    /// Deletes a row that may already have been partially deleted.
+    pub fn redo_delete(&mut self, row: NonZeroU32) -> Result<()> {
+        if self.serial.value(row).is_some() {
+            if !self.fragment.blanks().contains(&row) {
+                self.fragment.insert_blank(row)?;
+            }
+            self.serial.delete(row);
+        }
+        Ok(())
+    }
+
+    /// Returns the rows and serial numbers that the next inserts will use, without using them up.
+    /// A row that is on the blank list more than once is handed out once.
+    pub fn reserve(&self, count: usize) -> Vec<(NonZeroU32, u32)> {
+        let serial = self.fragment.serial();
+        let max_row = self.max_row;
+        let mut seen = HashSet::new();
+        self.fragment
+            .blanks()
+            .into_iter()
+            .rev()
+            .filter(|row| seen.insert(*row))
+            .chain((1..).map(|i| unsafe { NonZeroU32::new_unchecked(max_row + i) }))
+            .take(count)
+            .zip(1..)
+            .map(|(row, i)| (row, serial + i))
+            .collect()
+    }
+
+    /// Makes the row exist with the specified serial number.
+    pub fn assign(&mut self, row: NonZeroU32, serial: u32) -> Result<()> {
+        self.fragment.remove_blank(row)?;
+        self.serial.update(row, &serial);
+        self.fragment.serial_raise(serial);
+        self.max_row = self.max_row.max(row.get());
+        Ok(())
+    }
+
+    pub(crate) fn index_mut(&mut self) -> &mut IdxFile<u32> {
+        &mut self.serial
+    }
+
+    pub fn blanks(&self) -> Vec<NonZeroU32> {
+        self.fragment.blanks()
     }
 
-    pub fn next_row(&mut self) -> NonZeroU32 {
-        let v = self.fragment.serial_increment().get();
-        if let Some(row) = self.fragment.pop() {
-            self.serial.update(row, &v);
-            row
-        } else {
-            self.serial.insert(&v)
+    /// Removes duplicated rows and rows in use from the blank list.
+    pub fn repair_blanks(&mut self) -> Result<()> {
+        let mut blanks = vec![];
+        for row in self.fragment.blanks() {
+            if !blanks.contains(&row) && self.serial.value(row).is_none() {
+                blanks.push(row);
+            }
         }
+        self.fragment.set_blanks(&blanks)?;
+        self.max_row = self.max_row();
+        Ok(())
     }
 }
@@ -0,0 +1,141 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use hashbrown::HashSet;
+
+use crate::{Data, Error, Result};
+
+/// Directory in the data directory that a snapshot is copied into before it replaces the data.
+const RESTORE_DIR: &str = "restore";
+
+/// File in the data directory that lists the entries of the snapshot while they replace the data.
+/// While it exists the replacement has not finished, and opening the data finishes it.
+const RESTORE_LIST: &str = "restore.list";
+
+/// Copies the files in the directory recursively, leaving out the write-ahead log and work directories.
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == "wal" || name == RESTORE_DIR || name == "rebuild" {
+            continue;
+        }
+        let to = to.join(&name);
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to)?;
+        } else {
+            fs::copy(entry.path(), to)?;
+        }
+    }
+    Ok(())
+}
+
+fn remove(path: &Path) -> Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Resolves the path to an absolute path without symbolic links, even if the end of it does not exist yet.
+fn resolve(path: &Path) -> Result<PathBuf> {
+    let path = std::path::absolute(path)?;
+    for ancestor in path.ancestors() {
+        if let Ok(resolved) = ancestor.canonicalize() {
+            return Ok(resolved.join(path.strip_prefix(ancestor).unwrap()));
+        }
+    }
+    Ok(path)
+}
+
+/// Replaces the entries of the data directory with the entries of the restore directory listed in the restore list.
+/// Entries that have already been moved are kept, so that a replacement that stopped partway can be run again.
+pub(crate) fn finish_restore(dir: &Path) -> Result<()> {
+    let list_path = dir.join(RESTORE_LIST);
+    if !list_path.exists() {
+        return Ok(());
+    }
+    let list = fs::read_to_string(&list_path)?;
+    let names: HashSet<&str> = list.lines().collect();
+    let restore_dir = dir.join(RESTORE_DIR);
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == RESTORE_DIR || name == RESTORE_LIST || names.contains(&*name.to_string_lossy()) {
+            continue;
+        }
+        remove(&entry.path())?;
+    }
+    for name in &names {
+        let from = restore_dir.join(name);
+        if from.exists() {
+            let to = dir.join(name);
+            if to.exists() {
+                remove(&to)?;
+            }
+            fs::rename(from, to)?;
+        }
+    }
+    if restore_dir.exists() {
+        fs::remove_dir_all(&restore_dir)?;
+    }
+    fs::remove_file(list_path)?;
+    Ok(())
+}
+
+impl Data {
+    /// Copies all index files and field directories to the specified directory.
+    /// Writes need `&mut self`, so the copy is taken at one instant while other readers keep working.
+    /// Files are copied rather than hard linked because the indexes are updated in place.
+    /// Returns an error if the directory is the data directory or inside it.
+    pub fn snapshot<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if resolve(path)?.starts_with(resolve(self.dir())?) {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "snapshot directory {} is inside the data directory",
+                    path.display()
+                ),
+            )));
+        }
+        copy_dir(self.dir(), path)
+    }
+
+    /// Replaces the data with a snapshot taken by [Data::snapshot].
+    /// The snapshot is copied and opened first, so the data is left as it was if the snapshot cannot be opened.
+    /// Once the copy is complete its entries are listed, so that if the process stops while they replace the data,
+    /// opening the data finishes the replacement.
+    pub fn restore<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let dir = self.dir().to_path_buf();
+        let restore_dir = dir.join(RESTORE_DIR);
+        if restore_dir.exists() {
+            fs::remove_dir_all(&restore_dir)?;
+        }
+        copy_dir(path.as_ref(), &restore_dir)?;
+        if let Err(e) = Data::try_new(&restore_dir, self.option.clone()) {
+            fs::remove_dir_all(&restore_dir)?;
+            return Err(e);
+        }
+
+        let mut list = String::new();
+        for entry in fs::read_dir(&restore_dir)? {
+            list += &entry?.file_name().to_string_lossy();
+            list.push('\n');
+        }
+        let mut tmp = dir.join(RESTORE_LIST).into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, list)?;
+        fs::rename(tmp, dir.join(RESTORE_LIST))?;
+        finish_restore(&dir)?;
+
+        *self = Data::try_new(dir, self.option.clone())?;
+        Ok(())
+    }
+}
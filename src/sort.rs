@@ -16,6 +16,7 @@ impl Debug for dyn CustomSort {
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct NoCustomSort {}
 
 impl CustomSort for NoCustomSort {
@@ -32,7 +33,7 @@ impl CustomSort for NoCustomSort {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum CustomOrderKey<C: CustomSort> {
     Serial,
     Row,
@@ -45,7 +46,7 @@ pub enum CustomOrderKey<C: CustomSort> {
 
 pub type OrderKey = CustomOrderKey<NoCustomSort>;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Order<C: CustomSort> {
     Asc(CustomOrderKey<C>),
     Desc(CustomOrderKey<C>),
@@ -54,11 +55,25 @@ pub enum Order<C: CustomSort> {
 impl Data {
     /// Sort search results.
     pub fn sort<C: CustomSort>(&self, rows: &RowSet, orders: &[Order<C>]) -> Vec<NonZeroU32> {
+        self.sort_with_limit(rows, orders, None)
+    }
+
+    /// Sort search results, stopping once at least the specified number of rows are in order.
+    pub(crate) fn sort_with_limit<C: CustomSort>(
+        &self,
+        rows: &RowSet,
+        orders: &[Order<C>],
+        limit: Option<usize>,
+    ) -> Vec<NonZeroU32> {
         let sub_orders = &orders[1..];
-        match &orders[0] {
-            Order::Asc(key) => self.sort_with_key(rows, key, sub_orders),
-            Order::Desc(key) => self.sort_with_key_desc(rows, key, sub_orders),
+        let mut sorted = match &orders[0] {
+            Order::Asc(key) => self.sort_with_key(rows, key, sub_orders, limit),
+            Order::Desc(key) => self.sort_with_key_desc(rows, key, sub_orders, limit),
+        };
+        if let Some(limit) = limit {
+            sorted.truncate(limit);
         }
+        sorted
     }
 
     fn subsort<C: CustomSort>(
@@ -68,8 +83,8 @@ impl Data {
     ) -> Vec<NonZeroU32> {
         let mut tmp = tmp;
         tmp.sort_by(|a, b| {
-            for i in 0..sub_orders.len() {
-                match &sub_orders[i] {
+            for order in sub_orders {
+                match order {
                     Order::Asc(order_key) => match order_key {
                         CustomOrderKey::Serial => {
                             return unsafe {
@@ -193,10 +208,11 @@ impl Data {
         triee: &IdxFileAvlTriee<T, I>,
         iter: impl Iterator<Item = NonZeroU32>,
         sub_orders: &[Order<C>],
+        limit: Option<usize>,
     ) -> Vec<NonZeroU32> {
-        if sub_orders.len() == 0 {
-            iter.filter_map(|row| rows.contains(&row).then_some(row))
-                .collect()
+        let limit = limit.unwrap_or(usize::MAX);
+        if sub_orders.is_empty() {
+            iter.filter(|row| rows.contains(row)).take(limit).collect()
         } else {
             let mut ret = Vec::new();
 
@@ -213,6 +229,9 @@ impl Data {
                                 self.subsort(tmp, sub_orders)
                             });
                             tmp = vec![];
+                            if ret.len() >= limit {
+                                break;
+                            }
                         }
                     } else {
                         ret.extend(tmp);
@@ -236,8 +255,9 @@ impl Data {
         rows: &RowSet,
         triee: &IdxFileAvlTriee<T, I>,
         sub_orders: &[Order<C>],
+        limit: Option<usize>,
     ) -> Vec<NonZeroU32> {
-        self.sort_with_triee_inner(rows, triee, triee.iter(), sub_orders)
+        self.sort_with_triee_inner(rows, triee, triee.iter(), sub_orders, limit)
     }
 
     fn sort_with_triee_desc<T: PartialEq, I: ?Sized, C: CustomSort>(
@@ -245,8 +265,9 @@ impl Data {
         rows: &RowSet,
         index: &IdxFileAvlTriee<T, I>,
         sub_orders: &[Order<C>],
+        limit: Option<usize>,
     ) -> Vec<NonZeroU32> {
-        self.sort_with_triee_inner(rows, index, index.desc_iter(), sub_orders)
+        self.sort_with_triee_inner(rows, index, index.desc_iter(), sub_orders, limit)
     }
 
     fn sort_with_key<C: CustomSort>(
@@ -254,27 +275,28 @@ impl Data {
         rows: &RowSet,
         key: &CustomOrderKey<C>,
         sub_orders: &[Order<C>],
+        limit: Option<usize>,
     ) -> Vec<NonZeroU32> {
         match key {
             CustomOrderKey::Serial => {
-                self.sort_with_triee::<u32, u32, C>(rows, &self.serial, &vec![])
+                self.sort_with_triee::<u32, u32, C>(rows, &self.serial, &[], limit)
             }
-            CustomOrderKey::Row => rows.into_iter().cloned().collect(),
+            CustomOrderKey::Row => rows.iter().collect(),
             CustomOrderKey::TermBegin => self.term_begin.as_ref().map_or_else(
-                || rows.into_iter().cloned().collect(),
-                |f| self.sort_with_triee(rows, f, sub_orders),
+                || rows.iter().collect(),
+                |f| self.sort_with_triee(rows, f, sub_orders, limit),
             ),
             CustomOrderKey::TermEnd => self.term_end.as_ref().map_or_else(
-                || rows.into_iter().cloned().collect(),
-                |f| self.sort_with_triee(rows, f, sub_orders),
+                || rows.iter().collect(),
+                |f| self.sort_with_triee(rows, f, sub_orders, limit),
             ),
-            CustomOrderKey::LastUpdated => self.term_end.as_ref().map_or_else(
-                || rows.into_iter().cloned().collect(),
-                |f| self.sort_with_triee(rows, f, sub_orders),
+            CustomOrderKey::LastUpdated => self.last_updated.as_ref().map_or_else(
+                || rows.iter().collect(),
+                |f| self.sort_with_triee(rows, f, sub_orders, limit),
             ),
             CustomOrderKey::Field(name) => self.fields.get(name).map_or_else(
-                || rows.into_iter().cloned().collect(),
-                |f| self.sort_with_triee(rows, f.as_ref(), sub_orders),
+                || rows.iter().collect(),
+                |f| self.sort_with_triee(rows, f.as_ref(), sub_orders, limit),
             ),
             CustomOrderKey::Custom(custom_order) => custom_order.asc(),
         }
@@ -285,27 +307,28 @@ impl Data {
         rows: &RowSet,
         key: &CustomOrderKey<C>,
         sub_orders: &[Order<C>],
+        limit: Option<usize>,
     ) -> Vec<NonZeroU32> {
         match key {
             CustomOrderKey::Serial => {
-                self.sort_with_triee_desc::<u32, u32, C>(rows, &self.serial, &vec![])
+                self.sort_with_triee_desc::<u32, u32, C>(rows, &self.serial, &[], limit)
             }
-            CustomOrderKey::Row => rows.into_iter().rev().cloned().collect(),
+            CustomOrderKey::Row => rows.iter().rev().collect(),
             CustomOrderKey::TermBegin => self.term_begin.as_ref().map_or_else(
-                || rows.into_iter().rev().cloned().collect(),
-                |f| self.sort_with_triee_desc(rows, f, sub_orders),
+                || rows.iter().rev().collect(),
+                |f| self.sort_with_triee_desc(rows, f, sub_orders, limit),
             ),
             CustomOrderKey::TermEnd => self.term_end.as_ref().map_or_else(
-                || rows.into_iter().rev().cloned().collect(),
-                |f| self.sort_with_triee_desc(rows, f, sub_orders),
+                || rows.iter().rev().collect(),
+                |f| self.sort_with_triee_desc(rows, f, sub_orders, limit),
             ),
             CustomOrderKey::LastUpdated => self.last_updated.as_ref().map_or_else(
-                || rows.into_iter().rev().cloned().collect(),
-                |f| self.sort_with_triee_desc(rows, f, sub_orders),
+                || rows.iter().rev().collect(),
+                |f| self.sort_with_triee_desc(rows, f, sub_orders, limit),
             ),
             CustomOrderKey::Field(name) => self.fields.get(name).map_or_else(
-                || rows.into_iter().rev().cloned().collect(),
-                |f| self.sort_with_triee_desc(rows, f.as_ref(), sub_orders),
+                || rows.iter().rev().collect(),
+                |f| self.sort_with_triee_desc(rows, f.as_ref(), sub_orders, limit),
             ),
             CustomOrderKey::Custom(custom_order) => custom_order.desc(),
         }
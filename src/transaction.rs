@@ -0,0 +1,248 @@
+use std::num::NonZeroU32;
+
+use hashbrown::{HashMap, HashSet};
+use idx_binary::{AvltrieeSearch, AvltrieeUpdate};
+
+use std::path::Path;
+
+use idx_file::IdxFile;
+use various_data_file::DataAddress;
+
+use crate::{
+    field::stored_value, has_node, wal::Operation, Activity, Data, FieldName, Result, Term,
+};
+
+enum TransactionOperation {
+    Insert {
+        activity: Activity,
+        term_begin: Term,
+        term_end: Term,
+        fields: HashMap<FieldName, Vec<u8>>,
+    },
+    Update {
+        row: NonZeroU32,
+        activity: Activity,
+        term_begin: Term,
+        term_end: Term,
+        fields: HashMap<FieldName, Vec<u8>>,
+    },
+    Delete {
+        row: NonZeroU32,
+    },
+}
+
+/// The values a row had before an operation was applied.
+#[derive(Default)]
+pub(crate) struct RowState {
+    serial: Option<u32>,
+    uuid: Option<u128>,
+    activity: Option<u8>,
+    term_begin: Option<u64>,
+    term_end: Option<u64>,
+    last_updated: Option<u64>,
+    fields: HashMap<FieldName, Vec<u8>>,
+}
+
+/// Buffers inserts, updates and deletes across rows and applies all of them or none of them.
+pub struct Transaction<'a> {
+    data: &'a mut Data,
+    operations: Vec<TransactionOperation>,
+}
+
+impl<'a> Transaction<'a> {
+    fn new(data: &'a mut Data) -> Self {
+        Self {
+            data,
+            operations: Vec::new(),
+        }
+    }
+
+    /// Buffers an insert.
+    pub fn insert(
+        &mut self,
+        activity: Activity,
+        term_begin: Term,
+        term_end: Term,
+        fields: HashMap<FieldName, Vec<u8>>,
+    ) {
+        self.operations.push(TransactionOperation::Insert {
+            activity,
+            term_begin,
+            term_end,
+            fields,
+        });
+    }
+
+    /// Buffers an update.
+    pub fn update(
+        &mut self,
+        row: NonZeroU32,
+        activity: Activity,
+        term_begin: Term,
+        term_end: Term,
+        fields: HashMap<FieldName, Vec<u8>>,
+    ) {
+        self.operations.push(TransactionOperation::Update {
+            row,
+            activity,
+            term_begin,
+            term_end,
+            fields,
+        });
+    }
+
+    /// Buffers a delete.
+    pub fn delete(&mut self, row: NonZeroU32) {
+        self.operations.push(TransactionOperation::Delete { row });
+    }
+
+    /// Applies all buffered operations. Returns the row of each operation in the order they were buffered.
+    /// If any operation fails, the operations already applied are undone and the error is returned.
+    pub async fn commit(self) -> Result<Vec<NonZeroU32>> {
+        let mut reserved = self
+            .data
+            .serial
+            .reserve(
+                self.operations
+                    .iter()
+                    .filter(|o| matches!(o, TransactionOperation::Insert { .. }))
+                    .count(),
+            )
+            .into_iter();
+        let mut rows = Vec::with_capacity(self.operations.len());
+        let mut operations = Vec::with_capacity(self.operations.len());
+        // A row deleted twice is only deleted once, so that it is not put on the blank list twice.
+        let mut deleted = HashSet::new();
+        for operation in self.operations {
+            let operation = match operation {
+                TransactionOperation::Insert {
+                    activity,
+                    term_begin,
+                    term_end,
+                    fields,
+                } => {
+                    let (row, serial) = reserved.next().unwrap();
+                    let image = self.data.image(
+                        row,
+                        Some(serial),
+                        activity,
+                        term_begin,
+                        term_end,
+                        fields,
+                    )?;
+                    Operation::Write { row, image }
+                }
+                TransactionOperation::Update {
+                    row,
+                    activity,
+                    term_begin,
+                    term_end,
+                    fields,
+                } => {
+                    let image = self
+                        .data
+                        .image(row, None, activity, term_begin, term_end, fields)?;
+                    Operation::Write { row, image }
+                }
+                TransactionOperation::Delete { row } => {
+                    if !deleted.insert(row) {
+                        rows.push(row);
+                        continue;
+                    }
+                    Operation::Delete { row }
+                }
+            };
+            rows.push(match &operation {
+                Operation::Write { row, .. } | Operation::Delete { row } => *row,
+            });
+            operations.push(operation);
+        }
+        self.data.commit(&operations)?;
+        Ok(rows)
+    }
+
+    /// Discards all buffered operations.
+    pub fn rollback(self) {}
+}
+
+impl Data {
+    /// Begins a [Transaction].
+    pub fn begin_transaction(&mut self) -> Transaction<'_> {
+        Transaction::new(self)
+    }
+
+    /// Reads the current values of the row, so that they can be restored if a later operation fails.
+    /// Indexes whose files have no room for the row have never held it, and are not read.
+    pub(crate) fn row_state(&self, row: NonZeroU32) -> RowState {
+        let dir = self.dir();
+        fn value<T: Ord + Clone>(index: &IdxFile<T>, path: &Path, row: NonZeroU32) -> Option<T> {
+            has_node::<T>(path, row)
+                .then(|| index.value(row).cloned())
+                .flatten()
+        }
+        macro_rules! value {
+            ($index:expr, $file:literal) => {
+                $index
+                    .as_ref()
+                    .and_then(|f| value(f, &dir.join($file), row))
+            };
+        }
+        RowState {
+            serial: value(&self.serial, &dir.join("serial.i"), row),
+            uuid: value!(self.uuid, "uuid.i"),
+            activity: value!(self.activity, "activity.i"),
+            term_begin: value!(self.term_begin, "term_begin.i"),
+            term_end: value!(self.term_end, "term_end.i"),
+            last_updated: value!(self.last_updated, "last_updated.i"),
+            fields: self
+                .fields
+                .iter()
+                .filter_map(|(name, field)| {
+                    stored_value(field, &self.fields_dir.join(name.as_str()), row)
+                        .map(|v| (name.clone(), v.to_vec()))
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn restore_row(&mut self, row: NonZeroU32, state: RowState) -> Result<()> {
+        let dir = self.dir().to_path_buf();
+        let serial_written =
+            has_node::<u32>(&dir.join("serial.i"), row) && self.serial.value(row).is_some();
+        if let Some(serial) = state.serial {
+            if !serial_written {
+                self.serial.assign(row, serial)?;
+            }
+        } else if serial_written {
+            self.serial.delete(row)?;
+        }
+        macro_rules! restore {
+            ($index:expr, $value:expr, $type:ty, $file:literal) => {
+                if let Some(ref mut f) = $index {
+                    if let Some(v) = $value {
+                        f.update(row, &v);
+                    } else if has_node::<$type>(&dir.join($file), row) {
+                        f.delete(row);
+                    }
+                }
+            };
+        }
+        restore!(self.uuid, state.uuid, u128, "uuid.i");
+        restore!(self.activity, state.activity, u8, "activity.i");
+        restore!(self.term_begin, state.term_begin, u64, "term_begin.i");
+        restore!(self.term_end, state.term_end, u64, "term_end.i");
+        restore!(self.last_updated, state.last_updated, u64, "last_updated.i");
+        for name in self.fields.keys().cloned().collect::<Vec<_>>() {
+            let value = state.fields.get(&name);
+            self.reindex_text(&name, row, value.map(Vec::as_slice));
+            let field = self.fields.get_mut(&name).unwrap();
+            if let Some(v) = value {
+                field.update(row, v);
+            } else if has_node::<DataAddress>(&self.fields_dir.join(name.as_str()).join(".i"), row)
+            {
+                field.delete(row);
+            }
+        }
+        Ok(())
+    }
+}
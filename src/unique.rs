@@ -0,0 +1,80 @@
+use std::num::NonZeroU32;
+
+use hashbrown::HashMap;
+use idx_binary::{AvltrieeIter, AvltrieeSearch};
+
+use crate::{wal::Operation, Data, Error, FieldName, Result};
+
+impl Data {
+    /// Makes the field reject a value that another row already has.
+    /// Returns an error if the field already has the same value in two rows.
+    pub fn set_unique(&mut self, name: &FieldName, unique: bool) -> Result<()> {
+        let Some(mut schema) = self.manifest.get(name).cloned() else {
+            return Err(Error::Schema(format!("field {} does not exist", name)));
+        };
+        if unique {
+            let field = self.fields.get(name).unwrap();
+            let mut before: Option<&[u8]> = None;
+            for row in field.as_ref().iter() {
+                let value = unsafe { field.value_unchecked(row) };
+                if before == Some(value) {
+                    return Err(Error::ConstraintViolation {
+                        field: name.clone(),
+                        row,
+                    });
+                }
+                before = Some(value);
+            }
+        }
+        schema.unique = unique;
+        self.manifest.insert(name.clone(), schema)
+    }
+
+    /// Returns an error if applying the operations in order would leave two rows with the same value in a unique field.
+    pub(crate) fn check_unique(&self, operations: &[Operation]) -> Result<()> {
+        for (name, schema) in self.manifest.fields() {
+            if !schema.unique {
+                continue;
+            }
+            // The value each row touched by the operations has after them.
+            let mut values: HashMap<NonZeroU32, Option<&Vec<u8>>> = HashMap::new();
+            for operation in operations {
+                match operation {
+                    Operation::Write { row, image } => {
+                        if let Some(value) = image.fields.get(name) {
+                            values.insert(*row, Some(value));
+                        }
+                    }
+                    Operation::Delete { row } => {
+                        values.insert(*row, None);
+                    }
+                }
+            }
+            let field = self.fields.get(name);
+            for (row, value) in &values {
+                let Some(value) = value else {
+                    continue;
+                };
+                if let Some((other, _)) = values
+                    .iter()
+                    .find(|(other, v)| *other != row && *v == &Some(*value))
+                {
+                    return Err(Error::ConstraintViolation {
+                        field: name.clone(),
+                        row: *other,
+                    });
+                }
+                if let Some(other) = field.and_then(|field| {
+                    AvltrieeIter::by(field, value)
+                        .find(|other| other != row && !values.contains_key(other))
+                }) {
+                    return Err(Error::ConstraintViolation {
+                        field: name.clone(),
+                        row: other,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
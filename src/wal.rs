@@ -0,0 +1,224 @@
+use std::{
+    fs,
+    io::{Read, Seek, SeekFrom, Write},
+    num::NonZeroU32,
+    path::Path,
+    sync::Arc,
+};
+
+use hashbrown::HashMap;
+
+use crate::{FieldName, Result};
+
+const U32_SIZE: usize = std::mem::size_of::<u32>();
+const HEADER_SIZE: usize = U32_SIZE * 2;
+const APPLIED: &[u8] = b"DONE";
+
+const TAG_WRITE: u8 = 1;
+const TAG_DELETE: u8 = 2;
+
+/// Every value written to a row, resolved before the write so that replaying it gives the same result.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RowImage {
+    /// The serial number of a newly inserted row.
+    pub(crate) serial: Option<u32>,
+    /// The UUID to give the row if it does not have one yet.
+    pub(crate) uuid: Option<u128>,
+    pub(crate) activity: u8,
+    pub(crate) term_begin: u64,
+    pub(crate) term_end: u64,
+    pub(crate) last_updated: u64,
+    pub(crate) fields: HashMap<FieldName, Vec<u8>>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Operation {
+    Write { row: NonZeroU32, image: RowImage },
+    Delete { row: NonZeroU32 },
+}
+
+/// Write-ahead log. Holds the operations being applied so that they can be replayed after the process stops.
+///
+/// Neither the log nor the index files are synced to disk, so the log covers a process that is killed, not a power loss.
+///
+/// The file holds at most one entry: `[payload length][checksum][payload]`, followed by `DONE` once the operations have been applied.
+pub(crate) struct Wal {
+    file: fs::File,
+    /// True while the entry has been recorded but neither marked as applied nor discarded.
+    unapplied: bool,
+}
+
+impl Wal {
+    /// Opens the log. Returns the operations of an entry that was written completely but not applied.
+    pub(crate) fn open<P: AsRef<Path>>(path: P) -> Result<(Self, Option<Vec<Operation>>)> {
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let pending = Self::pending(&bytes);
+        Ok((
+            Self {
+                file,
+                unapplied: pending.is_some(),
+            },
+            pending,
+        ))
+    }
+
+    fn pending(bytes: &[u8]) -> Option<Vec<Operation>> {
+        if bytes.len() < HEADER_SIZE {
+            return None;
+        }
+        let len = u32::from_le_bytes(bytes[..U32_SIZE].try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(bytes[U32_SIZE..HEADER_SIZE].try_into().unwrap());
+        let payload = bytes.get(HEADER_SIZE..HEADER_SIZE + len)?;
+        if checksum != Self::checksum(payload) || bytes[HEADER_SIZE + len..].starts_with(APPLIED) {
+            return None;
+        }
+        Self::decode(payload)
+    }
+
+    /// Returns the operations of the entry if it was recorded but neither applied nor discarded,
+    /// as happens when undoing a failed operation fails too. They have to be replayed before the entry is replaced.
+    pub(crate) fn unapplied(&mut self) -> Result<Option<Vec<Operation>>> {
+        if !self.unapplied {
+            return Ok(None);
+        }
+        let mut bytes = Vec::new();
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.read_to_end(&mut bytes)?;
+        Ok(Self::pending(&bytes))
+    }
+
+    /// Records operations before they are applied. Replaces the previous entry.
+    pub(crate) fn append(&mut self, operations: &[Operation]) -> Result<()> {
+        let mut payload = Vec::new();
+        Self::encode(operations, &mut payload);
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len());
+        bytes.extend((payload.len() as u32).to_le_bytes());
+        bytes.extend(Self::checksum(&payload).to_le_bytes());
+        bytes.extend(payload);
+
+        self.file.set_len(0)?;
+        self.file.write_all(&bytes)?;
+        self.unapplied = true;
+        Ok(())
+    }
+
+    /// Marks the entry as applied.
+    pub(crate) fn applied(&mut self) -> Result<()> {
+        self.file.write_all(APPLIED)?;
+        self.unapplied = false;
+        Ok(())
+    }
+
+    /// Discards the entry.
+    pub(crate) fn clear(&mut self) -> Result<()> {
+        self.file.set_len(0)?;
+        self.unapplied = false;
+        Ok(())
+    }
+
+    fn checksum(bytes: &[u8]) -> u32 {
+        bytes.iter().fold(0x811c9dc5, |hash, b| {
+            (hash ^ *b as u32).wrapping_mul(0x01000193)
+        })
+    }
+
+    fn encode(operations: &[Operation], bytes: &mut Vec<u8>) {
+        bytes.extend((operations.len() as u32).to_le_bytes());
+        for operation in operations {
+            match operation {
+                Operation::Write { row, image } => {
+                    bytes.push(TAG_WRITE);
+                    bytes.extend(row.get().to_le_bytes());
+                    bytes.extend(image.serial.unwrap_or(0).to_le_bytes());
+                    bytes.extend(image.uuid.unwrap_or(0).to_le_bytes());
+                    bytes.push(image.activity);
+                    bytes.extend(image.term_begin.to_le_bytes());
+                    bytes.extend(image.term_end.to_le_bytes());
+                    bytes.extend(image.last_updated.to_le_bytes());
+                    bytes.extend((image.fields.len() as u32).to_le_bytes());
+                    for (name, value) in &image.fields {
+                        bytes.extend((name.len() as u32).to_le_bytes());
+                        bytes.extend(name.as_bytes());
+                        bytes.extend((value.len() as u32).to_le_bytes());
+                        bytes.extend(value);
+                    }
+                }
+                Operation::Delete { row } => {
+                    bytes.push(TAG_DELETE);
+                    bytes.extend(row.get().to_le_bytes());
+                }
+            }
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Vec<Operation>> {
+        let mut reader = Reader { bytes };
+        let count = reader.u32()?;
+        let mut operations = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let tag = reader.take(1)?[0];
+            let row = NonZeroU32::new(reader.u32()?)?;
+            operations.push(match tag {
+                TAG_WRITE => {
+                    let serial = reader.u32()?;
+                    let uuid = u128::from_le_bytes(reader.take(16)?.try_into().ok()?);
+                    let activity = reader.take(1)?[0];
+                    let term_begin = reader.u64()?;
+                    let term_end = reader.u64()?;
+                    let last_updated = reader.u64()?;
+                    let fields_count = reader.u32()?;
+                    let mut fields = HashMap::new();
+                    for _ in 0..fields_count {
+                        let len = reader.u32()? as usize;
+                        let name = String::from_utf8(reader.take(len)?.to_vec()).ok()?;
+                        let len = reader.u32()? as usize;
+                        fields.insert(Arc::new(name), reader.take(len)?.to_vec());
+                    }
+                    Operation::Write {
+                        row,
+                        image: RowImage {
+                            serial: (serial != 0).then_some(serial),
+                            uuid: (uuid != 0).then_some(uuid),
+                            activity,
+                            term_begin,
+                            term_end,
+                            last_updated,
+                            fields,
+                        },
+                    }
+                }
+                TAG_DELETE => Operation::Delete { row },
+                _ => return None,
+            });
+        }
+        Some(operations)
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        (self.bytes.len() >= len).then(|| {
+            let (head, tail) = self.bytes.split_at(len);
+            self.bytes = tail;
+            head
+        })
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+}
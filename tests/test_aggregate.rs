@@ -0,0 +1,74 @@
+#[cfg(test)]
+#[test]
+fn test_aggregate() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-aggregate/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_price = FieldName::new("price".into());
+    let field_shop = FieldName::new("shop".into());
+    let field_stock = FieldName::new("stock".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        data.create_typed_field(&field_price, FieldType::I64)
+            .unwrap();
+        for (activity, price, shop, stock) in [
+            (Activity::Active, 300, "north", "9"),
+            (Activity::Active, -20, "south", "12"),
+            (Activity::Active, 150, "north", "100"),
+            (Activity::Inactive, 1000, "south", "1"),
+            (Activity::Active, 50, "south", "30"),
+        ] {
+            data.insert(
+                activity,
+                Term::Default,
+                Term::Default,
+                [
+                    (field_price.clone(), FieldValue::I64(price).into()),
+                    (field_shop.clone(), shop.into()),
+                    (field_stock.clone(), stock.into()),
+                ]
+                .into(),
+            )
+            .await;
+        }
+
+        let search = data.search_activity(Activity::Active);
+        let aggregate = search.aggregate(&field_price).await;
+        assert_eq!(aggregate.count, 4);
+        assert_eq!(aggregate.sum, 480.0);
+        assert_eq!(aggregate.avg(), Some(120.0));
+        assert_eq!(aggregate.min, Some(FieldValue::I64(-20)));
+        assert_eq!(aggregate.max, Some(FieldValue::I64(300)));
+        assert_eq!(search.min(&field_price).await, Some(FieldValue::I64(-20)));
+        assert_eq!(search.max(&field_price).await, Some(FieldValue::I64(300)));
+
+        let aggregate = search.aggregate(&field_stock).await;
+        assert_eq!(aggregate.sum, 151.0);
+        assert_eq!(aggregate.min, Some(FieldValue::Bytes(b"9".to_vec())));
+        assert_eq!(aggregate.max, Some(FieldValue::Bytes(b"100".to_vec())));
+
+        let groups = search.aggregate_by(&field_price, &field_shop).await;
+        assert_eq!(groups.len(), 2);
+        let north = &groups[&b"north".to_vec()];
+        assert_eq!(north.count, 2);
+        assert_eq!(north.sum, 450.0);
+        assert_eq!(north.min, Some(FieldValue::I64(150)));
+        assert_eq!(north.max, Some(FieldValue::I64(300)));
+        let south = &groups[&b"south".to_vec()];
+        assert_eq!(south.count, 2);
+        assert_eq!(south.avg(), Some(15.0));
+        assert_eq!(south.min, Some(FieldValue::I64(-20)));
+        assert_eq!(south.max, Some(FieldValue::I64(50)));
+
+        let missing = FieldName::new("missing".into());
+        let aggregate = search.aggregate(&missing).await;
+        assert_eq!(aggregate, search::Aggregate::default());
+        assert_eq!(aggregate.avg(), None);
+        assert!(search.aggregate_by(&missing, &field_shop).await.is_empty());
+    });
+}
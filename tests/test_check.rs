@@ -0,0 +1,129 @@
+#[cfg(test)]
+#[test]
+fn test_check() {
+    use std::{fs, num::NonZeroU32, path::Path};
+
+    use versatile_data::*;
+
+    let dir = Path::new("./vd-test-check/");
+    if dir.exists() {
+        fs::remove_dir_all(dir).unwrap();
+    }
+    let field_name = FieldName::new("num".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for i in 1..=10 {
+            if i == 10 {
+                fs::copy(dir.join("activity.i"), dir.join("activity.i.old")).unwrap();
+            }
+            data.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), i.to_string().into())].into(),
+            )
+            .await;
+        }
+        data.delete(3.try_into().unwrap()).await;
+        assert!(data.check().is_ok());
+    });
+
+    // The activity index loses row 10 and still has the deleted row 3, and the blank list gets a duplicate and a row in use.
+    fs::rename(dir.join("activity.i.old"), dir.join("activity.i")).unwrap();
+    let mut blanks = fs::read(dir.join("serial.f")).unwrap();
+    blanks.extend(3u32.to_ne_bytes());
+    blanks.extend(5u32.to_ne_bytes());
+    fs::write(dir.join("serial.f"), blanks).unwrap();
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        let row10 = NonZeroU32::new(10).unwrap();
+
+        let report = data.check();
+        assert_eq!(report.duplicated_blanks, vec![NonZeroU32::new(3).unwrap()]);
+        assert_eq!(report.used_blanks, vec![NonZeroU32::new(5).unwrap()]);
+        assert_eq!(report.missing_rows, vec![("activity".into(), vec![row10])]);
+        assert_eq!(
+            report.orphan_rows,
+            vec![("activity".into(), vec![NonZeroU32::new(3).unwrap()])]
+        );
+        assert!(report.broken_indexes.is_empty());
+
+        assert_eq!(data.repair().unwrap(), report);
+        assert!(data.check().is_ok());
+        assert_eq!(data.activity(row10), Some(Activity::Active));
+
+        // Row 3 is reused once, and the next row is a new one.
+        let a = data
+            .insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                Default::default(),
+            )
+            .await;
+        let b = data
+            .insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                Default::default(),
+            )
+            .await;
+        assert_eq!(a.get(), 3);
+        assert_eq!(b.get(), 11);
+        assert_eq!(data.all().len(), 11);
+        assert_eq!(data.field_bytes(5.try_into().unwrap(), &field_name), b"5");
+        assert!(data.check().is_ok());
+    });
+
+    // Swapping the nodes of rows 4 and 5 leaves their children and parents linking to the wrong rows.
+    let swap = |path: &Path, unit: usize| {
+        let mut bytes = fs::read(path).unwrap();
+        let (four, five) = bytes[unit * 4..unit * 6].split_at_mut(unit);
+        four.swap_with_slice(five);
+        fs::write(path, bytes).unwrap();
+    };
+    swap(
+        &dir.join("term_begin.i"),
+        std::mem::size_of::<idx_file::AvltrieeNode<u64>>(),
+    );
+    let field_dir = dir.join("fields").join(field_name.as_str());
+    swap(
+        &field_dir.join(".i"),
+        std::mem::size_of::<idx_file::AvltrieeNode<various_data_file::DataAddress>>(),
+    );
+
+    let mut data = Data::new(dir, DataOption::default());
+    let report = data.check();
+    assert_eq!(
+        report.broken_indexes,
+        vec!["term_begin".to_string(), format!("fields/{}", field_name)]
+    );
+    assert_eq!(data.repair().unwrap(), report);
+    assert!(data.check().is_ok());
+    assert_eq!(data.field_bytes(4.try_into().unwrap(), &field_name), b"5");
+    assert_eq!(
+        data.result_field(&field_name, &search::Field::Match(b"5".to_vec())),
+        [NonZeroU32::new(4).unwrap()].into_iter().collect()
+    );
+    drop(data);
+
+    // A process that stopped after deleting the old field directory finishes replacing it when the data is opened.
+    let rebuild_dir = dir.join("rebuild");
+    fs::create_dir_all(&rebuild_dir).unwrap();
+    for file in fs::read_dir(&field_dir).unwrap() {
+        let file = file.unwrap();
+        fs::copy(file.path(), rebuild_dir.join(file.file_name())).unwrap();
+    }
+    fs::remove_dir_all(&field_dir).unwrap();
+    let mut manifest = fs::read_to_string(dir.join("manifest")).unwrap();
+    manifest += &format!("\treplace\t{}\n", field_name);
+    fs::write(dir.join("manifest"), manifest).unwrap();
+
+    let data = Data::new(dir, DataOption::default());
+    assert!(!rebuild_dir.exists());
+    assert_eq!(data.field_bytes(4.try_into().unwrap(), &field_name), b"5");
+    assert!(data.check().is_ok());
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+#[test]
+fn test_count() {
+    use std::sync::Arc;
+
+    use versatile_data::*;
+
+    let dir = "./vd-test-count/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_color = FieldName::new("color".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for (activity, color) in [
+            (Activity::Active, "red"),
+            (Activity::Active, "blue"),
+            (Activity::Inactive, "red"),
+            (Activity::Active, "red"),
+            (Activity::Active, "green"),
+        ] {
+            data.insert(
+                activity,
+                Term::Default,
+                Term::Default,
+                [(field_color.clone(), color.into())].into(),
+            )
+            .await;
+        }
+        data.delete(4.try_into().unwrap()).await;
+
+        let red = search::Field::Match(b"red".to_vec());
+        let yellow = search::Field::Match(b"yellow".to_vec());
+        let min_g = search::Field::Min(b"g".to_vec());
+        let max_c = search::Field::Max(b"c".to_vec());
+        let partial = search::Field::Partial(Arc::new("e".into()));
+
+        assert_eq!(data.begin_search().count().await, 4);
+        assert!(data.begin_search().exists().await);
+
+        assert_eq!(
+            data.search_field(field_color.clone(), &red).count().await,
+            2
+        );
+        assert_eq!(
+            data.search_field(field_color.clone(), &min_g).count().await,
+            3
+        );
+        assert_eq!(
+            data.search_field(field_color.clone(), &partial)
+                .count()
+                .await,
+            4
+        );
+        assert_eq!(data.search_activity(Activity::Inactive).count().await, 1);
+        assert_eq!(
+            data.search_field(field_color.clone(), &red)
+                .search_activity(Activity::Active)
+                .count()
+                .await,
+            1
+        );
+        assert_eq!(data.search_default().count().await, 3);
+
+        assert!(data.search_field(field_color.clone(), &red).exists().await);
+        assert!(
+            !data
+                .search_field(field_color.clone(), &yellow)
+                .exists()
+                .await
+        );
+        assert!(
+            !data
+                .search_field(field_color.clone(), &red)
+                .search_activity(Activity::Inactive)
+                .search_field(field_color.clone(), &max_c)
+                .exists()
+                .await
+        );
+        assert_eq!(
+            data.search_field(field_color.clone(), &yellow)
+                .count()
+                .await,
+            0
+        );
+    });
+}
@@ -0,0 +1,159 @@
+#[cfg(test)]
+#[test]
+fn test_cursor() {
+    use std::num::NonZeroU32;
+
+    use versatile_data::*;
+
+    let dir = "./vd-test-cursor/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_score = FieldName::new("score".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for i in 1..=20 {
+            data.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_score.clone(), (i % 5).to_string().into())].into(),
+            )
+            .await;
+        }
+
+        let mut expected: Vec<NonZeroU32> = data.all().into_iter().collect();
+        expected.sort_by_key(|row| (row.get() % 5, row.get()));
+
+        let mut pages = vec![];
+        let mut cursor: Option<Cursor> = None;
+        loop {
+            let (page, next) = data
+                .begin_search()
+                .limit(3)
+                .result_page(
+                    Order::Asc(OrderKey::Field(field_score.clone())),
+                    cursor.as_ref(),
+                )
+                .await;
+            assert!(page.len() <= 3);
+            pages.extend(page);
+            if next.is_none() {
+                break;
+            }
+            cursor = next.and_then(|c| Cursor::from_bytes(&c.to_bytes()));
+        }
+        assert_eq!(pages, expected);
+
+        // Rows inserted before the cursor do not shift the next page.
+        let (first, cursor) = data
+            .begin_search()
+            .limit(4)
+            .result_page(Order::Desc(OrderKey::Field(field_score.clone())), None)
+            .await;
+        assert_eq!(
+            first,
+            [19, 14, 9, 4].map(|r| r.try_into().unwrap()).to_vec()
+        );
+        data.insert(
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_score.clone(), b"4".to_vec())].into(),
+        )
+        .await;
+        data.delete(18.try_into().unwrap()).await;
+        let (second, _) = data
+            .begin_search()
+            .limit(4)
+            .result_page(
+                Order::Desc(OrderKey::Field(field_score.clone())),
+                cursor.as_ref(),
+            )
+            .await;
+        assert_eq!(
+            second,
+            [13, 8, 3, 17].map(|r| r.try_into().unwrap()).to_vec()
+        );
+
+        let (page, cursor) = data
+            .search_activity(Activity::Active)
+            .limit(5)
+            .result_page(Order::Asc(OrderKey::Serial), None)
+            .await;
+        assert_eq!(page.len(), 5);
+        let (page, _) = data
+            .search_activity(Activity::Active)
+            .limit(5)
+            .result_page(Order::Asc(OrderKey::Serial), cursor.as_ref())
+            .await;
+        assert_eq!(
+            page.into_iter()
+                .map(|row| *data.serial(row))
+                .collect::<Vec<_>>(),
+            vec![6, 7, 8, 9, 10]
+        );
+
+        #[derive(Clone)]
+        struct Reverse(Vec<NonZeroU32>);
+        impl CustomSort for Reverse {
+            fn compare(&self, a: NonZeroU32, b: NonZeroU32) -> std::cmp::Ordering {
+                b.cmp(&a)
+            }
+            fn asc(&self) -> Vec<NonZeroU32> {
+                self.0.iter().rev().cloned().collect()
+            }
+            fn desc(&self) -> Vec<NonZeroU32> {
+                self.0.clone()
+            }
+        }
+        let reverse = Reverse(data.all().into_iter().collect());
+
+        // A limit of 0 returns an empty page without a cursor in every order.
+        for page in [
+            data.begin_search()
+                .limit(0)
+                .result_page(Order::Asc(OrderKey::Field(field_score.clone())), None)
+                .await,
+            data.begin_search()
+                .limit(0)
+                .result_page(Order::Desc(OrderKey::Row), None)
+                .await,
+            data.begin_search()
+                .limit(0)
+                .result_page(Order::Asc(CustomOrderKey::Custom(reverse.clone())), None)
+                .await,
+        ] {
+            assert_eq!(page, (vec![], None));
+        }
+
+        let (page, cursor) = data
+            .begin_search()
+            .limit(2)
+            .result_page(Order::Asc(CustomOrderKey::Custom(reverse.clone())), None)
+            .await;
+        assert_eq!(page, [21, 20].map(|r| r.try_into().unwrap()).to_vec());
+        let cursor = cursor.unwrap();
+        let (page, _) = data
+            .begin_search()
+            .limit(2)
+            .result_page(
+                Order::Asc(CustomOrderKey::Custom(reverse.clone())),
+                Some(&cursor),
+            )
+            .await;
+        assert_eq!(page, [19, 17].map(|r| r.try_into().unwrap()).to_vec());
+
+        // The row of the cursor left the result, so its place in the custom order is lost.
+        data.delete(20.try_into().unwrap()).await;
+        let (page, next) = data
+            .begin_search()
+            .limit(2)
+            .result_page(Order::Asc(CustomOrderKey::Custom(reverse)), Some(&cursor))
+            .await;
+        assert!(page.is_empty());
+        assert!(next.is_none());
+    });
+}
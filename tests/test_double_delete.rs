@@ -0,0 +1,63 @@
+#[cfg(test)]
+#[test]
+fn test_double_delete() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-double-delete/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_name = FieldName::new("name".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        let mut rows = vec![];
+        for name in ["a", "b", "c"] {
+            rows.push(
+                data.insert(
+                    Activity::Active,
+                    Term::Default,
+                    Term::Default,
+                    [(field_name.clone(), name.into())].into(),
+                )
+                .await,
+            );
+        }
+
+        data.delete(rows[0]).await;
+        data.delete(rows[0]).await;
+
+        let mut transaction = data.begin_transaction();
+        transaction.delete(rows[1]);
+        transaction.delete(rows[1]);
+        assert_eq!(transaction.commit().await.unwrap(), vec![rows[1], rows[1]]);
+
+        let mut transaction = data.begin_transaction();
+        for name in ["d", "e", "f"] {
+            transaction.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), name.into())].into(),
+            );
+        }
+        let mut inserted = transaction.commit().await.unwrap();
+        inserted.sort();
+        inserted.dedup();
+        assert_eq!(inserted.len(), 3);
+        assert!(inserted.contains(&rows[0]) && inserted.contains(&rows[1]));
+
+        let a = data
+            .insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), "g".into())].into(),
+            )
+            .await;
+        assert!(!inserted.contains(&a));
+        assert_eq!(data.all().len(), 5);
+        assert!(data.check().is_ok());
+    });
+}
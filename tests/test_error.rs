@@ -0,0 +1,43 @@
+#[cfg(test)]
+#[test]
+fn test_error() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-error/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    std::fs::create_dir_all(dir).unwrap();
+
+    let file = "./vd-test-error/file";
+    std::fs::write(file, b"").unwrap();
+    assert!(matches!(
+        Data::try_new(file, DataOption::default()),
+        Err(Error::Io(_))
+    ));
+
+    let corrupted = "./vd-test-error/corrupted/";
+    std::fs::create_dir_all(corrupted).unwrap();
+    std::fs::write("./vd-test-error/corrupted/serial.i", b"abc").unwrap();
+    assert!(matches!(
+        Data::try_new(corrupted, DataOption::default()),
+        Err(Error::Corrupted { .. })
+    ));
+
+    let mut data = Data::try_new("./vd-test-error/data/", DataOption::default()).unwrap();
+    let field_test = FieldName::new("test".into());
+    futures::executor::block_on(async {
+        let row = data
+            .try_insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_test.clone(), b"TEST".to_vec())].into(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(data.field_bytes(row, &field_test), b"TEST");
+        data.try_delete(row).await.unwrap();
+        assert!(data.all().is_empty());
+    });
+}
@@ -0,0 +1,76 @@
+#[cfg(test)]
+#[test]
+fn test_explain() {
+    use std::sync::Arc;
+
+    use versatile_data::*;
+
+    let dir = "./vd-test-explain/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_name = FieldName::new("name".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for i in 1..=50 {
+            data.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), format!("name{}", i).into())].into(),
+            )
+            .await;
+        }
+
+        let partial = search::Field::Partial(Arc::new("2".into()));
+        let name_12 = search::Field::Match(b"name12".to_vec());
+        let search = data
+            .search_field(field_name.clone(), &partial)
+            .search_activity(Activity::Active)
+            .search_field(field_name.clone(), &name_12);
+        let plan = search.explain().await;
+        assert_eq!(plan.rows, search.result().await.len());
+        assert_eq!(plan.rows, 1);
+
+        let steps: Vec<_> = plan
+            .steps
+            .iter()
+            .map(|step| (step.access, step.evaluation, step.estimate, step.rows))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (
+                    search::Access::Lookup,
+                    search::Evaluation::Lookup,
+                    Some(1),
+                    1
+                ),
+                (
+                    search::Access::Lookup,
+                    search::Evaluation::Filter,
+                    Some(50),
+                    1
+                ),
+                (
+                    search::Access::FullScan,
+                    search::Evaluation::Filter,
+                    None,
+                    1
+                ),
+            ]
+        );
+        assert!(plan.steps[0].condition.contains("Match"));
+
+        let text = plan.to_string();
+        assert!(text.starts_with("1 rows in "));
+        assert!(text.contains("1. lookup by AVL lookup: "));
+        assert!(text.contains("3. filter by full scan: "));
+
+        let plan = data.begin_search().explain().await;
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.rows, 50);
+    });
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+#[test]
+fn test_facet() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-facet/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_color = FieldName::new("color".into());
+    let field_size = FieldName::new("size".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for (activity, color, size) in [
+            (Activity::Active, "red", "10"),
+            (Activity::Active, "blue", "9"),
+            (Activity::Inactive, "red", "10"),
+            (Activity::Active, "green", "9"),
+            (Activity::Active, "red", "100"),
+            (Activity::Inactive, "blue", "9"),
+        ] {
+            data.insert(
+                activity,
+                Term::Default,
+                Term::Default,
+                [
+                    (field_color.clone(), color.into()),
+                    (field_size.clone(), size.into()),
+                ]
+                .into(),
+            )
+            .await;
+        }
+
+        assert_eq!(
+            data.distinct(&field_color),
+            [
+                (b"blue".as_ref(), 2),
+                (b"green".as_ref(), 1),
+                (b"red".as_ref(), 3)
+            ]
+        );
+        assert_eq!(
+            data.distinct(&field_size),
+            [
+                (b"9".as_ref(), 3),
+                (b"10".as_ref(), 2),
+                (b"100".as_ref(), 1)
+            ]
+        );
+
+        assert_eq!(
+            data.search_activity(Activity::Inactive)
+                .facets(&field_color)
+                .await,
+            [(b"blue".as_ref(), 1), (b"red".as_ref(), 1)]
+        );
+        assert_eq!(
+            data.begin_search().facets(&field_size).await,
+            data.distinct(&field_size)
+        );
+
+        data.delete(5.try_into().unwrap()).await;
+        assert_eq!(
+            data.distinct(&field_size),
+            [(b"9".as_ref(), 3), (b"10".as_ref(), 2)]
+        );
+        assert!(data.distinct(&FieldName::new("missing".into())).is_empty());
+    });
+}
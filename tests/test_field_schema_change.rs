@@ -0,0 +1,94 @@
+#[cfg(test)]
+#[test]
+fn test_field_schema_change() {
+    use std::path::Path;
+
+    use versatile_data::*;
+
+    let dir = "./vd-test-field-schema-change/";
+    if Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_age = FieldName::new("age".into());
+    let field_years = FieldName::new("years".into());
+    let field_note = FieldName::new("note".into());
+    let field_old = FieldName::new("old".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        data.create_typed_field(&field_age, FieldType::U64).unwrap();
+        let row = data
+            .insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [
+                    (field_age.clone(), FieldValue::U64(20).into()),
+                    (field_note.clone(), b"memo".to_vec()),
+                ]
+                .into(),
+            )
+            .await;
+
+        assert!(data.rename_field(&field_age, &field_note).is_err());
+        assert!(data.drop_field(&field_years).is_err());
+
+        // The manifest cannot be written, so the fields are left as they were.
+        let blocker = Path::new(dir).join("manifest.tmp");
+        std::fs::create_dir(&blocker).unwrap();
+        assert!(data.rename_field(&field_age, &field_years).is_err());
+        assert!(data.drop_field(&field_note).is_err());
+        assert_eq!(data.field_num(row, &field_age), 20.0);
+        assert_eq!(data.field_bytes(row, &field_note), b"memo");
+        assert!(data.manifest().get(&field_age).is_some());
+        assert!(data.manifest().get(&field_years).is_none());
+        assert!(data.manifest().get(&field_note).is_some());
+        std::fs::remove_dir(&blocker).unwrap();
+
+        data.rename_field(&field_age, &field_years).unwrap();
+        assert!(data.fields().get(&field_age).is_none());
+        assert_eq!(data.field_type(&field_years), FieldType::U64);
+        assert_eq!(data.field_num(row, &field_years), 20.0);
+        assert_eq!(
+            data.search_field(
+                field_years.clone(),
+                &search::Field::Match(FieldValue::U64(20).into())
+            )
+            .result()
+            .await
+            .len(),
+            1
+        );
+
+        data.drop_field(&field_note).unwrap();
+        assert!(data.fields().get(&field_note).is_none());
+        assert!(!Path::new(dir).join("fields/note").exists());
+        assert!(data.check().is_ok());
+
+        // The field can be created again after it is dropped.
+        data.update(
+            row,
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_note.clone(), b"new".to_vec())].into(),
+        )
+        .await;
+        assert_eq!(data.field_bytes(row, &field_note), b"new");
+    });
+
+    // The process stopped after recording a rename in the manifest and before renaming the directory.
+    let manifest_path = Path::new(dir).join("manifest");
+    let manifest = std::fs::read_to_string(&manifest_path)
+        .unwrap()
+        .replace("years\t", "old\t")
+        + "\trename\tyears\told\n";
+    std::fs::write(&manifest_path, manifest).unwrap();
+
+    let data = Data::new(dir, DataOption::default());
+    assert!(data.fields().get(&field_years).is_none());
+    assert_eq!(data.field_type(&field_old), FieldType::U64);
+    assert_eq!(data.field_num(1.try_into().unwrap(), &field_old), 20.0);
+    assert_eq!(Manifest::read(dir).unwrap().fields().len(), 2);
+}
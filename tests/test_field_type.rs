@@ -0,0 +1,98 @@
+#[cfg(test)]
+#[test]
+fn test_field_type() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-field-type/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_i64 = FieldName::new("i64".into());
+    let field_f64 = FieldName::new("f64".into());
+    let field_bool = FieldName::new("bool".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        data.create_typed_field(&field_i64, FieldType::I64).unwrap();
+        data.create_typed_field(&field_f64, FieldType::F64).unwrap();
+        data.create_typed_field(&field_bool, FieldType::Bool)
+            .unwrap();
+        assert!(data
+            .create_typed_field(&field_i64, FieldType::String)
+            .is_err());
+
+        for (i, f) in [(10, 2.5), (-100, -0.5), (9, 1e10), (-5, -3.0), (0, 0.0)] {
+            data.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [
+                    (field_i64.clone(), FieldValue::I64(i).into()),
+                    (field_f64.clone(), FieldValue::F64(f).into()),
+                    (field_bool.clone(), FieldValue::Bool(i > 0).into()),
+                ]
+                .into(),
+            )
+            .await;
+        }
+        assert!(data
+            .try_insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_i64.clone(), b"10".to_vec())].into(),
+            )
+            .await
+            .is_err());
+        assert_eq!(data.all().len(), 5);
+    });
+
+    futures::executor::block_on(async {
+        let data = Data::new(dir, DataOption::default());
+        assert_eq!(data.field_type(&field_i64), FieldType::I64);
+
+        let rows = data.all();
+        let sorted: Vec<_> = data
+            .sort(&rows, &[Order::Asc(OrderKey::Field(field_i64.clone()))])
+            .into_iter()
+            .map(|row| data.field_num(row, &field_i64))
+            .collect();
+        assert_eq!(sorted, vec![-100.0, -5.0, 0.0, 9.0, 10.0]);
+
+        let sorted: Vec<_> = data
+            .sort(&rows, &[Order::Desc(OrderKey::Field(field_f64.clone()))])
+            .into_iter()
+            .map(|row| data.field_value(row, &field_f64).unwrap())
+            .collect();
+        assert_eq!(
+            sorted,
+            [1e10, 2.5, 0.0, -0.5, -3.0].map(FieldValue::F64).to_vec()
+        );
+
+        let found = data
+            .search_field(
+                field_i64.clone(),
+                &search::Field::Range(FieldValue::I64(-10).into(), FieldValue::I64(9).into()),
+            )
+            .result()
+            .await;
+        let mut values: Vec<_> = found
+            .into_iter()
+            .map(|row| data.field_num(row, &field_i64))
+            .collect();
+        values.sort_by(f64::total_cmp);
+        assert_eq!(values, vec![-5.0, 0.0, 9.0]);
+
+        assert_eq!(
+            data.search_field(
+                field_bool.clone(),
+                &search::Field::Match(FieldValue::Bool(true).into())
+            )
+            .result()
+            .await
+            .len(),
+            2
+        );
+    });
+}
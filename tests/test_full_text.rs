@@ -0,0 +1,160 @@
+#[cfg(test)]
+#[test]
+fn test_full_text() {
+    use std::sync::Arc;
+
+    use versatile_data::*;
+
+    let dir = "./vd-test-full-text/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_body = FieldName::new("body".into());
+    let field_price = FieldName::new("price".into());
+
+    let words = |text: &str| search::Field::Words(Arc::new(text.into()));
+    let phrase = |text: &str| search::Field::Phrase(Arc::new(text.into()));
+    let prefix = |text: &str| search::Field::WordForward(Arc::new(text.into()));
+    let rows = |rows: &[u32]| -> RowSet {
+        rows.iter()
+            .map(|r| std::num::NonZeroU32::new(*r).unwrap())
+            .collect()
+    };
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        data.create_typed_field(&field_price, FieldType::I64)
+            .unwrap();
+        for body in [
+            "The quick brown fox jumps over the lazy dog.",
+            "A quick brown dog.",
+            "Brown, brown, brown: the fox is BROWN!",
+            "Foxes are quick.",
+        ] {
+            data.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_body.clone(), body.into())].into(),
+            )
+            .await;
+        }
+
+        let conditions = [
+            (words("fox brown"), rows(&[1, 3])),
+            (words("DOG quick"), rows(&[1, 2])),
+            (words("cat"), rows(&[])),
+            (words(""), rows(&[])),
+            (phrase("quick brown"), rows(&[1, 2])),
+            (phrase("brown fox"), rows(&[1])),
+            (phrase("the lazy dog"), rows(&[1])),
+            (phrase("dog lazy"), rows(&[])),
+            (prefix("fox"), rows(&[1, 3, 4])),
+            (prefix("Qu"), rows(&[1, 2, 4])),
+        ];
+        let scanned: Vec<_> = conditions
+            .iter()
+            .map(|(condition, _)| data.result_field(&field_body, condition))
+            .collect();
+        assert!(data
+            .search_field(field_body.clone(), &conditions[0].0)
+            .explain()
+            .await
+            .steps
+            .iter()
+            .all(|step| step.access == search::Access::FullScan));
+
+        data.set_full_text(&field_body, true).unwrap();
+        for ((condition, expected), scanned) in conditions.iter().zip(scanned) {
+            assert_eq!(&scanned, expected, "{:?}", condition);
+            assert_eq!(
+                &data.result_field(&field_body, condition),
+                expected,
+                "{:?}",
+                condition
+            );
+            let search = data.search_field(field_body.clone(), condition);
+            assert_eq!(&search.stream().collect::<RowSet>(), expected);
+            assert_eq!(search.count().await, expected.len());
+        }
+        let plan = data
+            .search_field(field_body.clone(), &conditions[0].0)
+            .explain()
+            .await;
+        assert_eq!(plan.steps[0].access, search::Access::Lookup);
+        assert_eq!(plan.steps[0].estimate, Some(2));
+
+        let ranked = data
+            .begin_search()
+            .search_field(field_body.clone(), &words("brown"))
+            .rank(&field_body, "brown fox")
+            .await;
+        assert_eq!(
+            ranked.iter().map(|(row, _)| row.get()).collect::<Vec<_>>(),
+            [3, 1, 2]
+        );
+        assert!(ranked[0].1 > ranked[1].1 && ranked[1].1 > ranked[2].1);
+        let limited = data
+            .search_field(field_body.clone(), &words("brown"))
+            .offset(1)
+            .limit(1)
+            .rank(&field_body, "brown fox")
+            .await;
+        assert_eq!(limited, ranked[1..2]);
+
+        data.update(
+            2.try_into().unwrap(),
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_body.clone(), "A slow red fox.".into())].into(),
+        )
+        .await;
+        data.delete(1.try_into().unwrap()).await;
+        assert_eq!(data.result_field(&field_body, &words("fox")), rows(&[2, 3]));
+        assert_eq!(data.result_field(&field_body, &words("quick")), rows(&[4]));
+        assert_eq!(data.result_field(&field_body, &prefix("SL")), rows(&[2]));
+
+        assert!(matches!(
+            data.set_full_text(&field_price, true),
+            Err(Error::Schema(_))
+        ));
+    });
+
+    let mut data = Data::new(dir, DataOption::default());
+    assert!(data.manifest().get(&field_body).unwrap().full_text);
+    assert_eq!(
+        data.result_field(&field_body, &phrase("red fox")),
+        rows(&[2])
+    );
+    let plan = futures::executor::block_on(
+        data.search_field(field_body.clone(), &phrase("red fox"))
+            .explain(),
+    );
+    assert_eq!(plan.steps[0].access, search::Access::Lookup);
+
+    futures::executor::block_on(async {
+        data.update(
+            2.try_into().unwrap(),
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_body.clone(), "A quick brown fox.".into())].into(),
+        )
+        .await;
+        assert_eq!(data.result_field(&field_body, &words("red")), rows(&[]));
+        assert_eq!(
+            data.result_field(&field_body, &phrase("brown fox")),
+            rows(&[2])
+        );
+        let ranked = data
+            .search_field(field_body.clone(), &words("fox"))
+            .rank(&field_body, "quick fox")
+            .await;
+        assert_eq!(
+            ranked.iter().map(|(row, _)| row.get()).collect::<Vec<_>>(),
+            [2, 3]
+        );
+    });
+}
@@ -0,0 +1,111 @@
+#[cfg(test)]
+#[test]
+fn test_limit() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-limit/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_num = FieldName::new("num".into());
+    let field_group = FieldName::new("group".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for i in 1..=30 {
+            data.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [
+                    (field_num.clone(), i.to_string().into()),
+                    (field_group.clone(), (i % 4).to_string().into()),
+                ]
+                .into(),
+            )
+            .await;
+        }
+
+        let orders = || {
+            vec![
+                Order::Asc(OrderKey::Field(field_group.clone())),
+                Order::Desc(OrderKey::Field(field_num.clone())),
+            ]
+        };
+        let all = data.begin_search().result_with_sort(orders()).await;
+        assert_eq!(all.len(), 30);
+
+        for offset in [0, 5, 7, 28, 40] {
+            let page = data
+                .begin_search()
+                .offset(offset)
+                .limit(5)
+                .result_with_sort(orders())
+                .await;
+            assert_eq!(
+                page,
+                all.iter().skip(offset).take(5).cloned().collect::<Vec<_>>()
+            );
+        }
+
+        let top = data
+            .search_activity(Activity::Active)
+            .limit(3)
+            .result_with_sort(vec![Order::Desc(OrderKey::Field(field_num.clone()))])
+            .await;
+        assert_eq!(
+            top.into_iter()
+                .map(|row| data.field_num(row, &field_num))
+                .collect::<Vec<_>>(),
+            vec![30.0, 29.0, 28.0]
+        );
+
+        let rows = data
+            .begin_search()
+            .offset(10)
+            .result_with_sort(vec![Order::Asc(OrderKey::Row)])
+            .await;
+        assert_eq!(rows.len(), 20);
+        assert_eq!(rows[0].get(), 11);
+
+        // Sorting by the time of the last update does not follow the end of the term.
+        let mut updated = vec![];
+        for term_end in [100, 200, 300] {
+            updated.push(
+                data.insert(
+                    Activity::Active,
+                    Term::Default,
+                    Term::Overwrite(term_end),
+                    [].into(),
+                )
+                .await,
+            );
+        }
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        data.update(
+            updated[0],
+            Activity::Active,
+            Term::Default,
+            Term::Overwrite(100),
+            [].into(),
+        )
+        .await;
+        let range = search::Number::Range(31..=33);
+        for limit in [None, Some(2)] {
+            let mut search = data.search_row(&range);
+            if let Some(limit) = limit {
+                search = search.limit(limit);
+            }
+            let rows = search
+                .result_with_sort(vec![Order::Asc(OrderKey::LastUpdated)])
+                .await;
+            assert_eq!(rows.len(), limit.unwrap_or(3));
+            assert_eq!(rows.contains(&updated[0]), limit.is_none());
+            let rows = search
+                .result_with_sort(vec![Order::Desc(OrderKey::LastUpdated)])
+                .await;
+            assert_eq!(rows[0], updated[0]);
+        }
+    });
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+#[test]
+fn test_manifest() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-manifest/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_age = FieldName::new("age".into());
+    let field_name = FieldName::new("first\tname".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        data.create_typed_field(&field_age, FieldType::U64).unwrap();
+        data.insert(
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [
+                (field_age.clone(), FieldValue::U64(20).into()),
+                (field_name.clone(), b"Noah".to_vec()),
+            ]
+            .into(),
+        )
+        .await;
+
+        let manifest = Manifest::read(dir).unwrap();
+        assert_eq!(manifest.fields().len(), 2);
+        assert_eq!(manifest.get(&field_age).unwrap().field_type, FieldType::U64);
+        assert_eq!(
+            manifest.get(&field_name).unwrap().field_type,
+            FieldType::Bytes
+        );
+        assert_eq!(manifest.get(&field_age), data.manifest().get(&field_age));
+    });
+
+    // Directories without a manifest get one listing their fields as bytes.
+    std::fs::remove_file("./vd-test-manifest/manifest").unwrap();
+    let data = Data::new(dir, DataOption::default());
+    assert_eq!(data.field_type(&field_age), FieldType::Bytes);
+    assert_eq!(Manifest::read(dir).unwrap().fields().len(), 2);
+}
@@ -0,0 +1,127 @@
+#[cfg(test)]
+#[test]
+fn test_ngram() {
+    use std::{num::NonZeroU32, sync::Arc};
+
+    use versatile_data::*;
+
+    let dir = "./vd-test-ngram/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_text = FieldName::new("text".into());
+    let field_price = FieldName::new("price".into());
+
+    let text = |text: &str| Arc::new(String::from(text));
+    let rows =
+        |rows: &[u32]| -> RowSet { rows.iter().map(|r| NonZeroU32::new(*r).unwrap()).collect() };
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        data.create_typed_field(&field_price, FieldType::I64)
+            .unwrap();
+        for value in [
+            "東京都渋谷区",
+            "京都府京都市",
+            "大阪府大阪市北区",
+            "東京タワー",
+            "Kyoto station",
+            "Tokyo station",
+            "",
+        ] {
+            data.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_text.clone(), value.into())].into(),
+            )
+            .await;
+        }
+
+        let conditions = [
+            (search::Field::Partial(text("京都")), rows(&[1, 2])),
+            (search::Field::Partial(text("京")), rows(&[1, 2, 4])),
+            (search::Field::Partial(text("京都府")), rows(&[2])),
+            (search::Field::Partial(text("府大阪市")), rows(&[3])),
+            (search::Field::Partial(text("東京都庁")), rows(&[])),
+            (search::Field::Partial(text("to station")), rows(&[5])),
+            (search::Field::Backward(text("区")), rows(&[1, 3])),
+            (search::Field::Backward(text("市")), rows(&[2])),
+            (search::Field::Backward(text("station")), rows(&[5, 6])),
+            (search::Field::Forward(text("東京")), rows(&[1, 4])),
+        ];
+        let scanned: Vec<_> = conditions
+            .iter()
+            .map(|(condition, _)| data.result_field(&field_text, condition))
+            .collect();
+
+        data.set_ngram(&field_text, true).unwrap();
+        for ((condition, expected), scanned) in conditions.iter().zip(scanned) {
+            assert_eq!(&scanned, expected, "{:?}", condition);
+            assert_eq!(
+                &data.result_field(&field_text, condition),
+                expected,
+                "{:?}",
+                condition
+            );
+            let search = data.search_field(field_text.clone(), condition);
+            assert_eq!(&search.stream().collect::<RowSet>(), expected);
+            assert_eq!(search.count().await, expected.len());
+        }
+        let partial = search::Field::Partial(text("京都"));
+        let plan = data
+            .search_field(field_text.clone(), &partial)
+            .explain()
+            .await;
+        assert_eq!(plan.steps[0].access, search::Access::Lookup);
+        assert_eq!(plan.steps[0].estimate, Some(2));
+
+        data.update(
+            NonZeroU32::new(4).unwrap(),
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_text.clone(), "京都タワー".into())].into(),
+        )
+        .await;
+        data.delete(NonZeroU32::new(1).unwrap()).await;
+        assert_eq!(data.result_field(&field_text, &partial), rows(&[2, 4]));
+        assert_eq!(
+            data.result_field(&field_text, &search::Field::Partial(text("東京"))),
+            rows(&[])
+        );
+
+        assert!(matches!(
+            data.set_ngram(&field_price, true),
+            Err(Error::Schema(_))
+        ));
+    });
+
+    let mut data = Data::new(dir, DataOption::default());
+    assert!(data.manifest().get(&field_text).unwrap().ngram);
+    let backward = search::Field::Backward(text("ワー"));
+    assert_eq!(data.result_field(&field_text, &backward), rows(&[4]));
+    let plan =
+        futures::executor::block_on(data.search_field(field_text.clone(), &backward).explain());
+    assert_eq!(plan.steps[0].access, search::Access::Lookup);
+
+    futures::executor::block_on(data.update(
+        NonZeroU32::new(4).unwrap(),
+        Activity::Active,
+        Term::Default,
+        Term::Default,
+        [(field_text.clone(), "大阪城".into())].into(),
+    ));
+    assert_eq!(data.result_field(&field_text, &backward), rows(&[]));
+    assert_eq!(
+        data.result_field(&field_text, &search::Field::Partial(text("大阪"))),
+        rows(&[3, 4])
+    );
+
+    data.set_ngram(&field_text, false).unwrap();
+    let plan =
+        futures::executor::block_on(data.search_field(field_text.clone(), &backward).explain());
+    assert_eq!(plan.steps[0].access, search::Access::FullScan);
+    assert_eq!(data.result_field(&field_text, &backward), rows(&[]));
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+#[test]
+fn test_not() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-not/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_category = FieldName::new("category".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for (activity, category) in [
+            (Activity::Active, "archived"),
+            (Activity::Active, "news"),
+            (Activity::Inactive, "news"),
+            (Activity::Active, "blog"),
+        ] {
+            data.insert(
+                activity,
+                Term::Default,
+                Term::Default,
+                [(field_category.clone(), category.into())].into(),
+            )
+            .await;
+        }
+
+        let archived = search::Field::Match(b"archived".to_vec());
+        let is_archived = Condition::Field(field_category.clone(), &archived);
+
+        let rows = data
+            .search_activity(Activity::Active)
+            .search(Condition::Not(&is_archived))
+            .result()
+            .await;
+        assert_eq!(rows, [2, 4].map(|r| r.try_into().unwrap()).into());
+
+        let rows = data
+            .begin_search()
+            .search(Condition::Not(&is_archived))
+            .result()
+            .await;
+        assert_eq!(rows.len(), 3);
+
+        let active = Condition::Activity(Activity::Active);
+        let rows = data.result_condition(&Condition::Not(&active)).await;
+        assert_eq!(rows, [3.try_into().unwrap()].into());
+
+        let conditions = vec![Condition::Not(&active), is_archived];
+        let rows = data
+            .begin_search()
+            .search(Condition::Wide(&conditions))
+            .result()
+            .await;
+        assert_eq!(rows, [1, 3].map(|r| r.try_into().unwrap()).into());
+    });
+}
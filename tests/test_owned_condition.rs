@@ -0,0 +1,85 @@
+#[cfg(test)]
+#[test]
+fn test_owned_condition() {
+    use std::sync::Arc;
+
+    use versatile_data::*;
+
+    let dir = "./vd-test-owned-condition/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_name = FieldName::new("name".into());
+    let field_tag = FieldName::new("tag".into());
+
+    let saved = {
+        let query = OwnedCondition::Narrow(vec![
+            OwnedCondition::Activity(Activity::Active),
+            OwnedCondition::Wide(vec![
+                OwnedCondition::Field(field_tag.clone(), search::Field::Match(b"a".to_vec())),
+                OwnedCondition::Field(
+                    field_name.clone(),
+                    search::Field::Forward(Arc::new("ki".into())),
+                ),
+            ]),
+            OwnedCondition::Not(Box::new(OwnedCondition::Row(search::Number::In(vec![1])))),
+        ]);
+        std::thread::spawn(move || serde_json::to_string(&query).unwrap())
+            .join()
+            .unwrap()
+    };
+    let query: OwnedCondition = serde_json::from_str(&saved).unwrap();
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for (activity, name, tag) in [
+            (Activity::Active, "kiwi", "a"),
+            (Activity::Active, "apple", "a"),
+            (Activity::Active, "kiyomi", "b"),
+            (Activity::Inactive, "banana", "a"),
+            (Activity::Active, "cherry", "c"),
+        ] {
+            data.insert(
+                activity,
+                Term::Default,
+                Term::Default,
+                [
+                    (field_name.clone(), name.into()),
+                    (field_tag.clone(), tag.into()),
+                ]
+                .into(),
+            )
+            .await;
+        }
+
+        let rows = data
+            .begin_search()
+            .search(query.as_condition())
+            .result()
+            .await;
+        assert_eq!(rows, [2, 3].map(|r| r.try_into().unwrap()).into());
+
+        let tag_a = search::Field::Match(b"a".to_vec());
+        let forward = search::Field::Forward(Arc::new("ki".into()));
+        let row_1 = search::Number::In(vec![1]);
+        let wide = vec![
+            Condition::Field(field_tag.clone(), &tag_a),
+            Condition::Field(field_name.clone(), &forward),
+        ];
+        let not = Condition::Row(&row_1);
+        let narrow = vec![
+            Condition::Activity(Activity::Active),
+            Condition::Wide(&wide),
+            Condition::Not(&not),
+        ];
+        let borrowed = Condition::Narrow(&narrow);
+        assert_eq!(OwnedCondition::from(&borrowed), query);
+        assert_eq!(data.begin_search().search(borrowed).result().await, rows);
+
+        let search = data.begin_search().search(query.as_condition());
+        let streamed: RowSet = search.stream().collect();
+        assert_eq!(streamed, rows);
+        assert_eq!(search.count().await, 2);
+    });
+}
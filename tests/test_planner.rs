@@ -0,0 +1,154 @@
+#[cfg(test)]
+#[test]
+fn test_planner() {
+    use std::{num::NonZeroU32, sync::Arc};
+
+    use versatile_data::*;
+
+    let dir = "./vd-test-planner/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_name = FieldName::new("name".into());
+    let field_group = FieldName::new("group".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(
+            dir,
+            DataOption {
+                allocation_lot: 10000,
+                ..DataOption::default()
+            },
+        );
+        for i in 1..=200 {
+            data.insert(
+                if i % 10 == 0 {
+                    Activity::Inactive
+                } else {
+                    Activity::Active
+                },
+                Term::Default,
+                Term::Default,
+                [
+                    (field_name.clone(), format!("name{}", i).into()),
+                    (field_group.clone(), (i % 4).to_string().into()),
+                ]
+                .into(),
+            )
+            .await;
+        }
+
+        let rows = |rows: &[u32]| -> RowSet {
+            rows.iter().map(|r| NonZeroU32::new(*r).unwrap()).collect()
+        };
+        let expect = |f: &dyn Fn(u32) -> bool| -> RowSet {
+            (1..=200)
+                .filter(|i| f(*i))
+                .map(|r| NonZeroU32::new(r).unwrap())
+                .collect()
+        };
+
+        let name_150 = search::Field::Match(b"name150".to_vec());
+        let partial_5 = search::Field::Partial(Arc::new("5".into()));
+        let group_1 = search::Field::Match(b"1".to_vec());
+        let group_2 = search::Field::Match(b"2".to_vec());
+        let range = search::Number::Range(100..=120);
+
+        let result = data
+            .search_field(field_name.clone(), &partial_5)
+            .search_field(field_name.clone(), &name_150)
+            .result()
+            .await;
+        assert_eq!(result, rows(&[150]));
+
+        let result = data
+            .search_field(field_name.clone(), &partial_5)
+            .search_field(field_group.clone(), &group_1)
+            .search_activity(Activity::Active)
+            .result()
+            .await;
+        assert_eq!(
+            result,
+            expect(&|i| i.to_string().contains('5') && i % 4 == 1 && i % 10 != 0)
+        );
+
+        let is_group_2 = Condition::Field(field_group.clone(), &group_2);
+        let result = data
+            .begin_search()
+            .search(Condition::Not(&is_group_2))
+            .search_row(&range)
+            .result()
+            .await;
+        assert_eq!(result, expect(&|i| (100..=120).contains(&i) && i % 4 != 2));
+
+        let result = data
+            .begin_search()
+            .search(Condition::Not(&is_group_2))
+            .search_field(field_name.clone(), &partial_5)
+            .result()
+            .await;
+        assert_eq!(
+            result,
+            expect(&|i| i.to_string().contains('5') && i % 4 != 2)
+        );
+
+        let either = vec![
+            Condition::Field(field_group.clone(), &group_1),
+            Condition::Field(field_group.clone(), &group_2),
+        ];
+        let result = data
+            .search_activity(Activity::Inactive)
+            .search(Condition::Wide(&either))
+            .result()
+            .await;
+        assert_eq!(result, expect(&|i| i % 10 == 0 && i % 4 == 2));
+
+        let result = data
+            .search_field(field_name.clone(), &name_150)
+            .search_field(field_group.clone(), &group_1)
+            .search_field(field_name.clone(), &partial_5)
+            .result()
+            .await;
+        assert!(result.is_empty());
+
+        // Estimates stop at a sample size, so conditions matching many rows are not counted to the end.
+        let mut transaction = data.begin_transaction();
+        for i in 1..=1500 {
+            transaction.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(
+                    field_group.clone(),
+                    if i % 3 == 0 { "small" } else { "big" }.into(),
+                )]
+                .into(),
+            );
+        }
+        transaction.commit().await.unwrap();
+        let big = search::Field::Match(b"big".to_vec());
+        let small = search::Field::Match(b"small".to_vec());
+
+        let search = data
+            .search_activity(Activity::Active)
+            .search_field(field_group.clone(), &big);
+        assert_eq!(search.result().await.len(), 1000);
+        let plan = search.explain().await;
+        assert_eq!(
+            plan.steps
+                .iter()
+                .map(|step| step.estimate)
+                .collect::<Vec<_>>(),
+            [Some(1000), Some(1000)]
+        );
+
+        let search = data
+            .search_activity(Activity::Active)
+            .search_field(field_group.clone(), &small);
+        assert_eq!(search.result().await.len(), 500);
+        let plan = search.explain().await;
+        assert_eq!(plan.steps[0].estimate, Some(500));
+        assert_eq!(plan.steps[1].evaluation, search::Evaluation::Filter);
+    });
+}
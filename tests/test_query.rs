@@ -0,0 +1,131 @@
+#[cfg(test)]
+#[test]
+fn test_query() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-query/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_status = FieldName::new("status".into());
+    let field_price = FieldName::new("price".into());
+    let field_title = FieldName::new("title".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        data.create_typed_field(&field_price, FieldType::I64)
+            .unwrap();
+        for (activity, status, price, title) in [
+            (Activity::Active, "open", 30, "foo bar"),
+            (Activity::Active, "open", 5, "a foo"),
+            (Activity::Active, "closed", 50, "foo"),
+            (Activity::Active, "open", 10, "baz"),
+            (Activity::Inactive, "open", 80, "food"),
+            (Activity::Active, "open", -3, "foo \"quoted\""),
+        ] {
+            data.insert(
+                activity,
+                Term::Default,
+                Term::Default,
+                [
+                    (field_status.clone(), status.into()),
+                    (field_price.clone(), FieldValue::I64(price).into()),
+                    (field_title.clone(), title.into()),
+                ]
+                .into(),
+            )
+            .await;
+        }
+        let rows = |rows: &[u32]| {
+            rows.iter()
+                .map(|r| (*r).try_into().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let query = data
+            .parse_query(
+                r#"status = "open" AND price >= 10 AND title ~ "foo" ORDER BY price DESC LIMIT 20"#,
+            )
+            .unwrap();
+        assert_eq!(query.limit, Some(20));
+        assert_eq!(query.result(&data).await, rows(&[5, 1]));
+
+        let query = data
+            .parse_query("activity = active and (price < 10 or status != open) order by row desc")
+            .unwrap();
+        assert_eq!(query.result(&data).await, rows(&[6, 3, 2]));
+
+        let query = data
+            .parse_query(r#"NOT `title` ^= foo AND price > -3 ORDER BY price LIMIT 1 OFFSET 1"#)
+            .unwrap();
+        assert_eq!(query.result(&data).await, rows(&[4]));
+
+        let query = data.parse_query(r#"title = "foo \"quoted\"""#).unwrap();
+        assert_eq!(query.result(&data).await, rows(&[6]));
+
+        let query = data.parse_query("row <= 2 OR row = 4").unwrap();
+        assert_eq!(query.search(&data).count().await, 3);
+
+        let query = data.parse_query("ORDER BY price").unwrap();
+        assert!(query.condition.is_none());
+        assert_eq!(query.result(&data).await, rows(&[6, 2, 4, 1, 3, 5]));
+
+        let error = |text: &str| match data.parse_query(text) {
+            Err(Error::Query { position, reason }) => (position, reason),
+            _ => panic!("{} should not parse", text),
+        };
+        assert_eq!(
+            error("price >= ten"),
+            (10, "ten is not a value of i64 field price".into())
+        );
+        assert_eq!(
+            error("price ~ 1"),
+            (7, "~ needs a text field but price is i64".into())
+        );
+        assert_eq!(error(r#"title = "foo"#), (9, "unterminated string".into()));
+        assert_eq!(error("title == foo"), (7, "unknown operator ==".into()));
+        assert_eq!(
+            error("title = foo LIMIT"),
+            (18, "expected a number of rows after LIMIT".into())
+        );
+        assert_eq!(error("(title = foo"), (13, "expected )".into()));
+        assert_eq!(
+            error("title = foo price = 1"),
+            (13, "expected AND, OR, ORDER BY, LIMIT or OFFSET".into())
+        );
+        assert_eq!(
+            error("activity > active"),
+            (10, "activity cannot be compared with >".into())
+        );
+        assert_eq!(
+            data.parse_query("title = #").unwrap_err().to_string(),
+            "invalid query at column 9: unexpected character #"
+        );
+
+        let dir = "./vd-test-query-disabled/";
+        if std::path::Path::new(dir).exists() {
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+        let data = Data::new(
+            dir,
+            DataOption {
+                uuid: false,
+                last_updated: false,
+                ..DataOption::default()
+            },
+        );
+        for (text, key) in [
+            ("last_updated > 0", "last_updated"),
+            ("uuid = 67e55044-10b1-426f-9247-bb680e5fe0c8", "uuid"),
+        ] {
+            match data.parse_query(text) {
+                Err(Error::Query { position, reason }) => assert_eq!(
+                    (position, reason),
+                    (1, format!("{} is disabled in the data options", key))
+                ),
+                _ => panic!("{} should not parse", text),
+            }
+        }
+    });
+}
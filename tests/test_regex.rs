@@ -0,0 +1,104 @@
+#[cfg(test)]
+#[test]
+fn test_regex() {
+    use std::num::NonZeroU32;
+
+    use versatile_data::*;
+
+    let dir = "./vd-test-regex/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_code = FieldName::new("code".into());
+    let rows =
+        |rows: &[u32]| -> RowSet { rows.iter().map(|r| NonZeroU32::new(*r).unwrap()).collect() };
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for code in [
+            "A-001",
+            "A-002",
+            "B-100",
+            "a-7",
+            "090-1234-5678",
+            "03-1234-5678",
+            "A10",
+            "A2",
+            "A100",
+            "AB-1",
+        ] {
+            data.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_code.clone(), code.into())].into(),
+            )
+            .await;
+        }
+
+        for (pattern, prefixes, expected) in [
+            (r"^A-\d{3}$", Some(vec!["A-"]), rows(&[1, 2])),
+            (r"^(A|B)-1", Some(vec!["A-", "B-"]), rows(&[3])),
+            (r"(?i)^a-", Some(vec!["A-", "a-"]), rows(&[1, 2, 4])),
+            (r"^A1", Some(vec!["A"]), rows(&[7, 9])),
+            (r"^A(B|-)", Some(vec!["A-", "AB"]), rows(&[1, 2, 10])),
+            (r"^\d{2,3}-\d{4}-\d{4}$", None, rows(&[5, 6])),
+            (r"\d{4}$", None, rows(&[5, 6])),
+            (r"^C", Some(vec!["C"]), rows(&[])),
+        ] {
+            let condition = search::Field::Regex(pattern.parse().unwrap());
+            let search::Field::Regex(ref compiled) = condition else {
+                unreachable!();
+            };
+            assert_eq!(
+                compiled.prefixes(),
+                prefixes
+                    .map(|prefixes| prefixes
+                        .into_iter()
+                        .map(|prefix| prefix.as_bytes().to_vec())
+                        .collect::<Vec<_>>())
+                    .as_deref(),
+                "{}",
+                pattern
+            );
+
+            let result = data.result_field(&field_code, &condition);
+            assert_eq!(result, expected, "{}", pattern);
+            let search = data.search_field(field_code.clone(), &condition);
+            assert_eq!(search.stream().collect::<RowSet>(), expected, "{}", pattern);
+            assert_eq!(search.count().await, expected.len(), "{}", pattern);
+
+            let plan = search.explain().await;
+            assert_eq!(
+                plan.steps[0].access,
+                if compiled.prefixes().is_some() {
+                    search::Access::RangeWalk
+                } else {
+                    search::Access::FullScan
+                },
+                "{}",
+                pattern
+            );
+        }
+
+        let pattern = search::Pattern::new(r"^A-\d+$").unwrap();
+        let condition = OwnedCondition::Narrow(vec![
+            OwnedCondition::Activity(Activity::Active),
+            OwnedCondition::Field(field_code.clone(), search::Field::Regex(pattern)),
+        ]);
+        let json = serde_json::to_string(&condition).unwrap();
+        assert!(json.contains(r#""^A-\\d+$""#));
+        let condition: OwnedCondition = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            data.begin_search()
+                .search(condition.as_condition())
+                .result()
+                .await,
+            rows(&[1, 2])
+        );
+
+        assert!(search::Pattern::new("^A(").is_err());
+        assert!(serde_json::from_str::<search::Field>(r#"{"Regex":"^A("}"#).is_err());
+    });
+}
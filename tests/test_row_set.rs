@@ -0,0 +1,80 @@
+#[cfg(test)]
+#[test]
+fn test_row_set() {
+    use std::num::NonZeroU32;
+
+    use versatile_data::*;
+
+    let rows =
+        |rows: &[u32]| -> RowSet { rows.iter().map(|r| NonZeroU32::new(*r).unwrap()).collect() };
+
+    let a = rows(&[1, 2, 3, 100_000]);
+    let b = rows(&[2, 3, 4]);
+    assert_eq!(&a & &b, rows(&[2, 3]));
+    assert_eq!(&a | &b, rows(&[1, 2, 3, 4, 100_000]));
+    assert_eq!(&a - &b, rows(&[1, 100_000]));
+    assert_eq!(a.len(), 4);
+    assert!(a.contains(&NonZeroU32::new(100_000).unwrap()));
+    assert_eq!(
+        a.iter().rev().map(|r| r.get()).collect::<Vec<_>>(),
+        [100_000, 3, 2, 1]
+    );
+
+    let mut c = a.clone();
+    c.retain(|r| r.get() % 2 == 1);
+    assert_eq!(c, rows(&[1, 3]));
+    assert_eq!(format!("{:?}", c), "{1, 3}");
+
+    let dir = "./vd-test-row-set/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_tag = FieldName::new("tag".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for tag in ["a", "b", "a", "c", "b"] {
+            data.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_tag.clone(), tag.into())].into(),
+            )
+            .await;
+        }
+
+        let tag_a = search::Field::Match(b"a".to_vec());
+        let tag_b = search::Field::Match(b"b".to_vec());
+        let wide = vec![
+            Condition::Field(field_tag.clone(), &tag_a),
+            Condition::Field(field_tag.clone(), &tag_b),
+        ];
+        let narrow = vec![
+            Condition::Field(field_tag.clone(), &tag_a),
+            Condition::Field(field_tag.clone(), &tag_b),
+        ];
+        let not_a = Condition::Field(field_tag.clone(), &tag_a);
+
+        let result = data
+            .begin_search()
+            .search(Condition::Wide(&wide))
+            .result()
+            .await;
+        assert_eq!(result, rows(&[1, 2, 3, 5]));
+
+        let result = data
+            .begin_search()
+            .search(Condition::Narrow(&narrow))
+            .result()
+            .await;
+        assert!(result.is_empty());
+
+        let result = data
+            .begin_search()
+            .search(Condition::Not(&not_a))
+            .result()
+            .await;
+        assert_eq!(result, rows(&[2, 4, 5]));
+    });
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+#[test]
+fn test_snapshot() {
+    use std::path::Path;
+
+    use versatile_data::*;
+
+    let dir = Path::new("./vd-test-snapshot/");
+    let backup = Path::new("./vd-test-snapshot-backup/");
+    for d in [dir, backup] {
+        if d.exists() {
+            std::fs::remove_dir_all(d).unwrap();
+        }
+    }
+    let field_name = FieldName::new("name".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        let noah = data
+            .insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), b"Noah".to_vec())].into(),
+            )
+            .await;
+        data.snapshot(backup).unwrap();
+        assert!(data.snapshot(dir).is_err());
+        assert!(data.snapshot(dir.join("backup")).is_err());
+        assert!(!dir.join("backup").exists());
+
+        data.update(
+            noah,
+            Activity::Inactive,
+            Term::Default,
+            Term::Default,
+            [(field_name.clone(), b"Liam".to_vec())].into(),
+        )
+        .await;
+        data.insert(
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_name.clone(), b"Olivia".to_vec())].into(),
+        )
+        .await;
+        assert_eq!(data.all().len(), 2);
+
+        let snapshot = Data::new(backup, DataOption::default());
+        assert_eq!(snapshot.all().len(), 1);
+        assert_eq!(snapshot.field_bytes(noah, &field_name), b"Noah");
+
+        data.restore(backup).unwrap();
+        assert_eq!(data.all().len(), 1);
+        assert_eq!(data.field_bytes(noah, &field_name), b"Noah");
+        assert_eq!(data.activity(noah), Some(Activity::Active));
+        assert_eq!(
+            data.search_field(field_name.clone(), &search::Field::Match(b"Noah".to_vec()))
+                .result()
+                .await
+                .len(),
+            1
+        );
+        assert!(data.check().is_ok());
+
+        let row = data
+            .insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), b"Emma".to_vec())].into(),
+            )
+            .await;
+        assert_eq!(*data.serial(row), 2);
+        assert_eq!(snapshot.all().len(), 1);
+    });
+
+    // The process stopped while the copied snapshot was replacing the data, after the fields had been moved.
+    fn copy_dir(from: &Path, to: &Path) {
+        std::fs::create_dir_all(to).unwrap();
+        for entry in std::fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            let to = to.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &to);
+            } else {
+                std::fs::copy(entry.path(), to).unwrap();
+            }
+        }
+    }
+    let restore_dir = dir.join("restore");
+    copy_dir(backup, &restore_dir);
+    let list: String = std::fs::read_dir(&restore_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap() + "\n")
+        .collect();
+    std::fs::write(dir.join("restore.list"), list).unwrap();
+    std::fs::remove_dir_all(dir.join("fields")).unwrap();
+    std::fs::rename(restore_dir.join("fields"), dir.join("fields")).unwrap();
+
+    let data = Data::new(dir, DataOption::default());
+    assert_eq!(data.all().len(), 1);
+    assert_eq!(
+        data.field_bytes(1.try_into().unwrap(), &field_name),
+        b"Noah"
+    );
+    assert!(data.check().is_ok());
+    assert!(!restore_dir.exists());
+    assert!(!dir.join("restore.list").exists());
+}
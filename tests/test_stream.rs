@@ -0,0 +1,73 @@
+#[cfg(test)]
+#[test]
+fn test_stream() {
+    use std::num::NonZeroU32;
+
+    use versatile_data::*;
+
+    let dir = "./vd-test-stream/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_num = FieldName::new("num".into());
+    let field_category = FieldName::new("category".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        for i in 1..=20 {
+            data.insert(
+                if i % 3 == 0 {
+                    Activity::Inactive
+                } else {
+                    Activity::Active
+                },
+                Term::Default,
+                Term::Default,
+                [
+                    (field_num.clone(), i.to_string().into()),
+                    (
+                        field_category.clone(),
+                        if i % 2 == 0 { "even" } else { "odd" }.into(),
+                    ),
+                ]
+                .into(),
+            )
+            .await;
+        }
+
+        let even = search::Field::Match(b"even".to_vec());
+        let min = search::Field::Min(b"5".to_vec());
+        let partial = search::Field::Partial(std::sync::Arc::new("1".into()));
+        let is_even = Condition::Field(field_category.clone(), &even);
+        let range = search::Number::Range(3..=8);
+        let inactive_or_even = vec![
+            Condition::Activity(Activity::Inactive),
+            Condition::Field(field_category.clone(), &even),
+        ];
+
+        let searches = [
+            data.search_activity(Activity::Active),
+            data.search_field(field_num.clone(), &min)
+                .search(Condition::Not(&is_even)),
+            data.search_field(field_num.clone(), &partial)
+                .search_activity(Activity::Active),
+            data.search_row(&range)
+                .search(Condition::Wide(&inactive_or_even)),
+            data.search_term(search::Term::In(u64::MAX)),
+            data.search_default(),
+        ];
+        for search in searches {
+            let streamed: RowSet = search.stream().collect();
+            assert_eq!(streamed, search.result().await);
+        }
+
+        let rows: Vec<NonZeroU32> = data
+            .search_field(field_num.clone(), &min)
+            .search_activity(Activity::Active)
+            .stream()
+            .take(3)
+            .collect();
+        assert_eq!(rows, [5, 7, 8].map(|r| r.try_into().unwrap()));
+    });
+}
@@ -0,0 +1,94 @@
+#[cfg(test)]
+#[test]
+fn test_transaction() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-transaction/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    std::fs::create_dir_all("./vd-test-transaction/fields/").unwrap();
+    // A file where the field directory should be makes creating that field fail.
+    std::fs::write("./vd-test-transaction/fields/broken", b"").unwrap();
+
+    let mut data = Data::new(dir, DataOption::default());
+    let field_name = FieldName::new("name".into());
+    let field_broken = FieldName::new("broken".into());
+
+    futures::executor::block_on(async {
+        let mut transaction = data.begin_transaction();
+        transaction.insert(
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_name.clone(), b"Noah".to_vec())].into(),
+        );
+        transaction.insert(
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_name.clone(), b"Liam".to_vec())].into(),
+        );
+        let rows = transaction.commit().await.unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(data.all().len(), 2);
+
+        let mut transaction = data.begin_transaction();
+        transaction.delete(rows[0]);
+        transaction.rollback();
+        assert_eq!(data.all().len(), 2);
+
+        let mut transaction = data.begin_transaction();
+        transaction.update(
+            rows[0],
+            Activity::Inactive,
+            Term::Default,
+            Term::Default,
+            [(field_name.clone(), b"Olivia".to_vec())].into(),
+        );
+        transaction.delete(rows[1]);
+        // Creating the field fails when the insert is applied, after the update and the delete have been applied.
+        transaction.insert(
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_broken.clone(), b"x".to_vec())].into(),
+        );
+        assert!(transaction.commit().await.is_err());
+
+        assert_eq!(data.all().len(), 2);
+        assert_eq!(data.field_bytes(rows[0], &field_name), b"Noah");
+        assert_eq!(data.activity(rows[0]), Some(Activity::Active));
+        assert_eq!(data.field_bytes(rows[1], &field_name), b"Liam");
+        assert_eq!(
+            data.search_field(field_name.clone(), &search::Field::Match(b"Liam".to_vec()))
+                .result()
+                .await
+                .len(),
+            1
+        );
+        assert!(data.check().is_ok());
+
+        // A commit rejected by a unique constraint leaves no new field behind.
+        let field_nickname = FieldName::new("nickname".into());
+        data.set_unique(&field_name, true).unwrap();
+        let mut transaction = data.begin_transaction();
+        transaction.insert(
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [
+                (field_name.clone(), b"Noah".to_vec()),
+                (field_nickname.clone(), b"N".to_vec()),
+            ]
+            .into(),
+        );
+        assert!(matches!(
+            transaction.commit().await,
+            Err(Error::ConstraintViolation { .. })
+        ));
+        assert!(data.manifest().get(&field_nickname).is_none());
+        assert!(!data.fields().contains_key(&field_nickname));
+        assert_eq!(data.all().len(), 2);
+    });
+}
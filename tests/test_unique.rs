@@ -0,0 +1,114 @@
+#[cfg(test)]
+#[test]
+fn test_unique() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-unique/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_email = FieldName::new("email".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        let noah = data
+            .insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_email.clone(), b"noah@example.com".to_vec())].into(),
+            )
+            .await;
+        let liam = data
+            .insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_email.clone(), b"noah@example.com".to_vec())].into(),
+            )
+            .await;
+        assert!(matches!(
+            data.set_unique(&field_email, true),
+            Err(Error::ConstraintViolation { .. })
+        ));
+        data.update(
+            liam,
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_email.clone(), b"liam@example.com".to_vec())].into(),
+        )
+        .await;
+        data.set_unique(&field_email, true).unwrap();
+        assert!(
+            Manifest::read(dir)
+                .unwrap()
+                .get(&field_email)
+                .unwrap()
+                .unique
+        );
+
+        match data
+            .try_insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_email.clone(), b"noah@example.com".to_vec())].into(),
+            )
+            .await
+        {
+            Err(Error::ConstraintViolation { field, row }) => {
+                assert_eq!(field, field_email);
+                assert_eq!(row, noah);
+            }
+            _ => panic!("duplicate value was inserted"),
+        }
+        assert!(data
+            .try_update(
+                liam,
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_email.clone(), b"noah@example.com".to_vec())].into(),
+            )
+            .await
+            .is_err());
+        // Writing the value the row already has is not a violation.
+        data.try_update(
+            noah,
+            Activity::Inactive,
+            Term::Default,
+            Term::Default,
+            [(field_email.clone(), b"noah@example.com".to_vec())].into(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(data.all().len(), 2);
+
+        // Two rows in one transaction cannot get the same value.
+        let mut transaction = data.begin_transaction();
+        for _ in 0..2 {
+            transaction.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_email.clone(), b"olivia@example.com".to_vec())].into(),
+            );
+        }
+        assert!(transaction.commit().await.is_err());
+        assert_eq!(data.all().len(), 2);
+
+        // A value freed earlier in the transaction can be used.
+        let mut transaction = data.begin_transaction();
+        transaction.delete(noah);
+        transaction.insert(
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_email.clone(), b"noah@example.com".to_vec())].into(),
+        );
+        transaction.commit().await.unwrap();
+        assert_eq!(data.all().len(), 2);
+    });
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+#[test]
+fn test_upsert() {
+    use versatile_data::*;
+
+    let dir = "./vd-test-upsert/";
+    if std::path::Path::new(dir).exists() {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    let field_id = FieldName::new("external_id".into());
+    let field_name = FieldName::new("name".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(dir, DataOption::default());
+        data.create_typed_field(&field_id, FieldType::String)
+            .unwrap();
+        assert!(data
+            .upsert(
+                &field_id,
+                b"A-1".to_vec(),
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), b"Noah".to_vec())].into(),
+            )
+            .await
+            .is_err());
+        data.set_unique(&field_id, true).unwrap();
+
+        let (row, created) = data
+            .upsert(
+                &field_id,
+                b"A-1".to_vec(),
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), b"Noah".to_vec())].into(),
+            )
+            .await
+            .unwrap();
+        assert!(created);
+        assert_eq!(data.field_bytes(row, &field_id), b"A-1");
+
+        let (updated, created) = data
+            .upsert(
+                &field_id,
+                b"A-1".to_vec(),
+                Activity::Inactive,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), b"Liam".to_vec())].into(),
+            )
+            .await
+            .unwrap();
+        assert!(!created);
+        assert_eq!(updated, row);
+        assert_eq!(data.field_bytes(row, &field_name), b"Liam");
+        assert_eq!(data.activity(row), Some(Activity::Inactive));
+
+        let uuid = create_uuid();
+        let (row, created) = data
+            .upsert_uuid(
+                uuid,
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), b"Olivia".to_vec())].into(),
+            )
+            .await
+            .unwrap();
+        assert!(created);
+        assert_eq!(data.uuid(row), Some(&uuid));
+
+        let (updated, created) = data
+            .upsert_uuid(
+                uuid,
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), b"Emma".to_vec())].into(),
+            )
+            .await
+            .unwrap();
+        assert!(!created);
+        assert_eq!(updated, row);
+        assert_eq!(data.field_bytes(row, &field_name), b"Emma");
+        assert_eq!(data.uuid(row), Some(&uuid));
+        assert_eq!(data.all().len(), 2);
+    });
+}
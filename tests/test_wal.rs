@@ -0,0 +1,196 @@
+#[cfg(test)]
+#[test]
+fn test_wal() {
+    use std::{fs, num::NonZeroU32, path::Path};
+
+    use versatile_data::*;
+
+    fn copy_dir(from: &Path, to: &Path) {
+        fs::create_dir_all(to).unwrap();
+        for entry in fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            let to = to.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &to);
+            } else {
+                fs::copy(entry.path(), to).unwrap();
+            }
+        }
+    }
+
+    type RowValues = (
+        NonZeroU32,
+        u32,
+        Option<u128>,
+        Option<Activity>,
+        Option<u64>,
+        Option<u64>,
+        Vec<u8>,
+        usize,
+    );
+
+    fn state(dir: &Path, field_name: &FieldName) -> Vec<RowValues> {
+        let data = Data::new(
+            dir,
+            DataOption {
+                wal: true,
+                ..DataOption::default()
+            },
+        );
+        assert!(data.check().is_ok());
+        futures::executor::block_on(async {
+            let mut state = vec![];
+            for row in data.all() {
+                let value = data.field_bytes(row, field_name).to_vec();
+                let found = data
+                    .search_field(field_name.clone(), &search::Field::Match(value.clone()))
+                    .result()
+                    .await
+                    .len();
+                state.push((
+                    row,
+                    *data.serial(row),
+                    data.uuid(row).cloned(),
+                    data.activity(row),
+                    data.term_begin(row).cloned(),
+                    data.term_end(row).cloned(),
+                    value,
+                    found,
+                ));
+            }
+            state
+        })
+    }
+
+    let root = Path::new("./vd-test-wal/");
+    if root.exists() {
+        fs::remove_dir_all(root).unwrap();
+    }
+    let before = root.join("before");
+    let after = root.join("after");
+    let work = root.join("work");
+    let field_name = FieldName::new("name".into());
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(
+            &before,
+            DataOption {
+                wal: true,
+                ..DataOption::default()
+            },
+        );
+        for name in ["Noah", "Liam", "Olivia"] {
+            data.insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), name.into())].into(),
+            )
+            .await;
+        }
+    });
+    copy_dir(&before, &after);
+    futures::executor::block_on(async {
+        let mut data = Data::new(
+            &after,
+            DataOption {
+                wal: true,
+                ..DataOption::default()
+            },
+        );
+        let mut transaction = data.begin_transaction();
+        transaction.update(
+            1.try_into().unwrap(),
+            Activity::Inactive,
+            Term::Overwrite(1),
+            Term::Default,
+            [(field_name.clone(), "Emma".into())].into(),
+        );
+        transaction.delete(2.try_into().unwrap());
+        transaction.insert(
+            Activity::Active,
+            Term::Default,
+            Term::Default,
+            [(field_name.clone(), "Liam".into())].into(),
+        );
+        transaction.commit().await.unwrap();
+    });
+    let wal = fs::read(after.join("wal")).unwrap();
+
+    let state_before = state(&before, &field_name);
+    let state_after = state(&after, &field_name);
+    assert_ne!(state_before, state_after);
+
+    // The process stops while writing the log: nothing or everything has been logged.
+    let mut complete = None;
+    for len in 0..=wal.len() {
+        if work.exists() {
+            fs::remove_dir_all(&work).unwrap();
+        }
+        copy_dir(&before, &work);
+        fs::write(work.join("wal"), &wal[..len]).unwrap();
+        let state = state(&work, &field_name);
+        if state == state_after {
+            complete.get_or_insert(len);
+        } else {
+            assert_eq!(state, state_before, "log truncated at {}", len);
+        }
+    }
+    let complete = complete.unwrap();
+
+    // The process stops while applying the log: any of the indexes may already have been written.
+    let mut units: Vec<Vec<_>> = vec![vec!["serial.i".into(), "serial.f".into()]];
+    for entry in fs::read_dir(&before).unwrap() {
+        let name = entry.unwrap().file_name().into_string().unwrap();
+        if name.ends_with(".i") && !name.starts_with("serial") {
+            units.push(vec![name]);
+        }
+    }
+    units.push(vec!["fields".into()]);
+    for written in 0..(1 << units.len()) {
+        if work.exists() {
+            fs::remove_dir_all(&work).unwrap();
+        }
+        copy_dir(&before, &work);
+        for (i, unit) in units.iter().enumerate() {
+            if written & (1 << i) != 0 {
+                for name in unit {
+                    let from = after.join(name);
+                    if from.is_dir() {
+                        fs::remove_dir_all(work.join(name)).unwrap();
+                        copy_dir(&from, &work.join(name));
+                    } else {
+                        fs::copy(from, work.join(name)).unwrap();
+                    }
+                }
+            }
+        }
+        fs::write(work.join("wal"), &wal[..complete]).unwrap();
+        assert_eq!(
+            state(&work, &field_name),
+            state_after,
+            "written {:b}",
+            written
+        );
+    }
+
+    futures::executor::block_on(async {
+        let mut data = Data::new(
+            &work,
+            DataOption {
+                wal: true,
+                ..DataOption::default()
+            },
+        );
+        let row = data
+            .insert(
+                Activity::Active,
+                Term::Default,
+                Term::Default,
+                [(field_name.clone(), "Ava".into())].into(),
+            )
+            .await;
+        assert_eq!(data.all().len(), state_after.len() + 1);
+        assert_eq!(*data.serial(row), 5);
+    });
+}